@@ -0,0 +1,451 @@
+//! The allocation behind every shared vector: a header followed by the elements.
+//! The allocator lives in the header too, so a handle stays a single pointer no matter which
+//! allocator the vector uses, and zero-sized allocators like `Global` cost nothing.
+//!
+//! Growing and shrinking keep about half of the capacity unused, as the `header-slice` vector
+//! this replaces did, but every allocation goes through the `Allocator` in the header.
+
+use crate::error::TryReserveError;
+use alloc::alloc::handle_alloc_error;
+use allocator_api2::alloc::Allocator;
+use core::alloc::Layout;
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+
+/// The smallest capacity a vector shrinks to, and the capacity of `new_in`.
+const MIN_CAP: usize = 8;
+
+#[repr(C)]
+pub struct Pair<A, B>(pub A, pub B);
+
+/// The header at the start of every shared vector.
+/// The length and capacity live here rather than in each handle so that a handle is a single
+/// pointer, and so every handle to the same vector agrees on them.
+#[derive(Debug)]
+pub struct Header<C, A> {
+    pub counter: C,
+    pub(super) len: usize,
+    pub(super) cap: usize,
+    /// Moved along with the rest of the header when the vector is reallocated.
+    pub(super) alloc: A,
+}
+
+/// A pointer to the header of a vector, followed by its elements.
+pub type NodePtr<C, T, A> = NonNull<Pair<Header<C, A>, MaybeUninit<T>>>;
+
+/// Panics like `alloc::vec::Vec` does when an infallible allocation fails.
+fn handle_reserve<T>(res: Result<T, TryReserveError>) -> T {
+    match res {
+        Ok(x) => x,
+        Err(TryReserveError::CapacityOverflow) => panic!("capacity overflow"),
+        Err(TryReserveError::AllocError { layout }) => handle_alloc_error(layout),
+    }
+}
+
+/// An owned vector with a header, in memory from the allocator stored in that header.
+/// Unlike the shared handles, this keeps its own length and capacity; `VecMut` writes them back
+/// into the header when it is done.
+pub struct HeaderVec<C, T, A: Allocator + Clone> {
+    ptr: NodePtr<C, T, A>,
+    len: usize,
+    cap: usize,
+}
+
+impl<C, T, A: Allocator + Clone> HeaderVec<C, T, A> {
+    /// Returns the layout of a vector with the given capacity.
+    pub(super) fn layout_for(cap: usize) -> Result<Layout, TryReserveError> {
+        let body = Layout::array::<T>(cap).map_err(|_| TryReserveError::CapacityOverflow)?;
+        let (layout, _) = Layout::new::<Header<C, A>>()
+            .extend(body)
+            .map_err(|_| TryReserveError::CapacityOverflow)?;
+        Ok(layout.pad_to_align())
+    }
+
+    /// Like `with_capacity_in`, but returns an error instead of panicking if allocation fails.
+    pub fn try_with_capacity_in(counter: C, cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let layout = Self::layout_for(cap)?;
+        let bytes = alloc
+            .allocate(layout)
+            .map_err(|_| TryReserveError::AllocError { layout })?;
+        let ptr = bytes.cast::<Pair<Header<C, A>, MaybeUninit<T>>>();
+        let head = Header {
+            counter,
+            len: 0,
+            cap,
+            alloc,
+        };
+        unsafe { ptr::write(ptr::addr_of_mut!((*ptr.as_ptr()).0), head) };
+        Ok(Self { ptr, len: 0, cap })
+    }
+
+    pub fn with_capacity_in(counter: C, cap: usize, alloc: A) -> Self {
+        handle_reserve(Self::try_with_capacity_in(counter, cap, alloc))
+    }
+
+    pub fn new_in(counter: C, alloc: A) -> Self {
+        Self::with_capacity_in(counter, MIN_CAP, alloc)
+    }
+
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(counter: C, iter: I, alloc: A) -> Self {
+        let iter = iter.into_iter();
+        let mut this = Self::with_capacity_in(counter, iter.size_hint().0, alloc);
+        this.extend(iter);
+        this
+    }
+
+    /// Copies `len` elements starting at `src` into a new vector.
+    /// Do not use or drop the originals after this.
+    pub unsafe fn copy_from_ptr_unsafe_in(counter: C, src: *mut T, len: usize, alloc: A) -> Self {
+        let mut this = Self::with_capacity_in(counter, len, alloc);
+        ptr::copy_nonoverlapping(src, this.body_ptr(), len);
+        this.len = len;
+        this
+    }
+
+    /// Returns the raw parts (pointer, length, capacity) without consuming the vector.
+    pub fn as_raw_parts(&mut self) -> (NodePtr<C, T, A>, usize, usize) {
+        (self.ptr, self.len, self.cap)
+    }
+
+    /// Returns the raw parts (pointer, length, capacity), to be passed to `from_raw_parts`.
+    pub fn into_raw_parts(self) -> (NodePtr<C, T, A>, usize, usize) {
+        let this = ManuallyDrop::new(self);
+        (this.ptr, this.len, this.cap)
+    }
+
+    pub unsafe fn from_raw_parts(ptr: NodePtr<C, T, A>, len: usize, cap: usize) -> Self {
+        Self { ptr, len, cap }
+    }
+
+    pub fn counter_mut(&mut self) -> &mut C {
+        unsafe { &mut (*self.ptr.as_ptr()).0.counter }
+    }
+
+    pub fn allocator(&self) -> &A {
+        unsafe { &(*self.ptr.as_ptr()).0.alloc }
+    }
+
+    fn body_ptr(&self) -> *mut T {
+        unsafe { ptr::addr_of_mut!((*self.ptr.as_ptr()).1) as *mut T }
+    }
+
+    pub fn capacity(&self) -> usize {
+        if mem::size_of::<T>() == 0 {
+            usize::MAX
+        } else {
+            self.cap
+        }
+    }
+
+    /// Reallocates to exactly `new_cap`, leaving the vector untouched on failure.
+    /// SAFETY: `new_cap` must not be less than the length.
+    pub(super) unsafe fn try_realloc_exact(
+        &mut self,
+        new_cap: usize,
+    ) -> Result<(), TryReserveError> {
+        if mem::size_of::<T>() == 0 || new_cap == self.cap {
+            return Ok(());
+        }
+        let old_layout = Self::layout_for(self.cap)?;
+        let new_layout = Self::layout_for(new_cap)?;
+        // the allocator inside the header moves along with it, so use a copy of it meanwhile
+        let alloc = self.allocator().clone();
+        let old_ptr = self.ptr.cast::<u8>();
+        let bytes = if new_layout.size() > old_layout.size() {
+            alloc.grow(old_ptr, old_layout, new_layout)
+        } else {
+            alloc.shrink(old_ptr, old_layout, new_layout)
+        };
+        let bytes = bytes.map_err(|_| TryReserveError::AllocError { layout: new_layout })?;
+        self.ptr = bytes.cast();
+        self.cap = new_cap;
+        Ok(())
+    }
+
+    unsafe fn realloc_exact(&mut self, new_cap: usize) {
+        handle_reserve(self.try_realloc_exact(new_cap));
+    }
+
+    /// Increases capacity so that about half of it is unused.
+    fn grow(&mut self, target_len: usize) {
+        let target_cap = target_len.saturating_mul(2).max(self.cap);
+        unsafe { self.realloc_exact(target_cap) }
+    }
+
+    /// Decreases capacity so that about half of it is unused.
+    /// SAFETY: `target_len` must not be less than the length.
+    unsafe fn shrink(&mut self, target_len: usize) {
+        let target_cap = (target_len * 2).max(MIN_CAP).min(self.cap);
+        self.realloc_exact(target_cap);
+    }
+
+    pub fn push(&mut self, val: T) {
+        if self.len == self.capacity() {
+            self.grow(self.len + 1);
+        }
+        unsafe { ptr::write(self.body_ptr().add(self.len), val) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let val = self.pop_in_place()?;
+        unsafe { self.shrink(self.len) };
+        Some(val)
+    }
+
+    /// Removes the last element without shrinking the allocation.
+    pub fn pop_in_place(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { ptr::read(self.body_ptr().add(self.len)) })
+    }
+
+    pub fn insert(&mut self, index: usize, val: T) {
+        assert!(index <= self.len, "insertion index is out of bounds");
+        self.grow(self.len + 1);
+        unsafe {
+            let target = self.body_ptr().add(index);
+            ptr::copy(target, target.add(1), self.len - index);
+            ptr::write(target, val);
+        }
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let val = unsafe {
+            let target = self.body_ptr().add(index);
+            let val = ptr::read(target);
+            ptr::copy(target.add(1), target, self.len - index - 1);
+            val
+        };
+        self.len -= 1;
+        unsafe { self.shrink(self.len) };
+        Some(val)
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let last = self.pop()?;
+        if index == self.len {
+            return Some(last);
+        }
+        Some(mem::replace(&mut self[index], last))
+    }
+
+    /// Shortens the vector to `new_len`. Panics if `new_len` is greater than the length.
+    pub fn truncate(&mut self, new_len: usize) {
+        assert!(new_len <= self.len);
+        if new_len == self.len {
+            return;
+        }
+        let tail = ptr::slice_from_raw_parts_mut(
+            unsafe { self.body_ptr().add(new_len) },
+            self.len - new_len,
+        );
+        // update the length first so a panicking destructor can't cause a double drop
+        self.len = new_len;
+        unsafe {
+            ptr::drop_in_place(tail);
+            self.shrink(new_len);
+        }
+    }
+
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        if new_len < self.len {
+            self.truncate(new_len);
+        } else {
+            for _ in self.len..new_len {
+                self.push(f());
+            }
+        }
+    }
+
+    /// Drops every element and reallocates so there is no excess capacity.
+    pub fn clear(&mut self) {
+        self.clear_in_place();
+        unsafe { self.realloc_exact(0) }
+    }
+
+    /// Drops every element without reallocating.
+    pub fn clear_in_place(&mut self) {
+        let body: *mut [T] = &mut **self;
+        self.len = 0;
+        unsafe { ptr::drop_in_place(body) };
+    }
+
+    /// Reserves room for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        let target_len = self.len.checked_add(additional).expect("capacity overflow");
+        if target_len > self.capacity() {
+            self.grow(target_len);
+        }
+    }
+
+    /// Reserves room for exactly `additional` more elements.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let target_len = self.len.checked_add(additional).expect("capacity overflow");
+        if target_len > self.capacity() {
+            unsafe { self.realloc_exact(target_len) };
+        }
+    }
+
+    /// Fallible counterpart to `reserve`.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let target_len = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        if target_len <= self.capacity() {
+            return Ok(());
+        }
+        let target_cap = target_len.checked_mul(2).unwrap_or(target_len);
+        // fall back to the exact capacity if the amortized one is too large
+        unsafe {
+            self.try_realloc_exact(target_cap)
+                .or_else(|_| self.try_realloc_exact(target_len))
+        }
+    }
+
+    /// Fallible counterpart to `reserve_exact`.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let target_len = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        if target_len <= self.capacity() {
+            return Ok(());
+        }
+        unsafe { self.try_realloc_exact(target_len) }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        unsafe { self.realloc_exact(self.len) }
+    }
+
+    /// Deallocates the vector without dropping the elements. The counter is dropped, since it
+    /// may carry data of its own.
+    pub unsafe fn dealloc_without_dropping(self) {
+        let this = ManuallyDrop::new(self);
+        ptr::drop_in_place(ptr::addr_of_mut!((*this.ptr.as_ptr()).0.counter));
+        // move the allocator out of the header before freeing it
+        let alloc = ptr::read(this.allocator());
+        let layout = match Self::layout_for(this.cap) {
+            Ok(layout) => layout,
+            Err(_) => unreachable!("the layout was valid when the vector was allocated"),
+        };
+        alloc.deallocate(this.ptr.cast(), layout);
+    }
+
+    pub fn into_values(self) -> IntoValues<C, T, A> {
+        IntoValues {
+            vec: ManuallyDrop::new(self),
+            index: 0,
+        }
+    }
+}
+
+impl<C, T: Clone, A: Allocator + Clone> HeaderVec<C, T, A> {
+    pub fn resize(&mut self, new_len: usize, val: T) {
+        self.resize_with(new_len, || val.clone());
+    }
+}
+
+impl<C, T: Copy, A: Allocator + Clone> HeaderVec<C, T, A> {
+    pub fn copy_from_slice_in(counter: C, src: &[T], alloc: A) -> Self {
+        unsafe { Self::copy_from_ptr_unsafe_in(counter, src.as_ptr() as *mut T, src.len(), alloc) }
+    }
+
+    pub fn extend_from_slice(&mut self, src: &[T]) {
+        self.reserve(src.len());
+        unsafe { ptr::copy_nonoverlapping(src.as_ptr(), self.body_ptr().add(self.len), src.len()) };
+        self.len += src.len();
+    }
+}
+
+impl<C, T, A: Allocator + Clone> Deref for HeaderVec<C, T, A> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.body_ptr(), self.len) }
+    }
+}
+
+impl<C, T, A: Allocator + Clone> DerefMut for HeaderVec<C, T, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.body_ptr(), self.len) }
+    }
+}
+
+impl<C, T, A: Allocator + Clone> Extend<T> for HeaderVec<C, T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<C, T, A: Allocator + Clone> Drop for HeaderVec<C, T, A> {
+    fn drop(&mut self) {
+        self.clear_in_place();
+        unsafe { ptr::read(self).dealloc_without_dropping() };
+    }
+}
+
+/// Iterator that moves the elements out of a `HeaderVec`, then deallocates it.
+pub struct IntoValues<C, T, A: Allocator + Clone> {
+    vec: ManuallyDrop<HeaderVec<C, T, A>>,
+    index: usize,
+}
+
+impl<C, T, A: Allocator + Clone> IntoValues<C, T, A> {
+    /// The elements that haven't been yielded yet.
+    fn rest(&mut self) -> *mut [T] {
+        &mut self.vec[self.index..]
+    }
+}
+
+impl<C, T, A: Allocator + Clone> Iterator for IntoValues<C, T, A> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.index >= self.vec.len {
+            return None;
+        }
+        let val = unsafe { ptr::read(self.vec.body_ptr().add(self.index)) };
+        self.index += 1;
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<C, T, A: Allocator + Clone> ExactSizeIterator for IntoValues<C, T, A> {}
+
+impl<C: Default, T: Clone, A: Allocator + Clone> Clone for IntoValues<C, T, A> {
+    fn clone(&self) -> Self {
+        let rest = &self.vec[self.index..];
+        let alloc = self.vec.allocator().clone();
+        HeaderVec::from_iter_in(C::default(), rest.iter().cloned(), alloc).into_values()
+    }
+}
+
+impl<C, T, A: Allocator + Clone> Drop for IntoValues<C, T, A> {
+    fn drop(&mut self) {
+        let rest = self.rest();
+        unsafe {
+            let vec = ManuallyDrop::take(&mut self.vec);
+            ptr::drop_in_place(rest);
+            vec.dealloc_without_dropping();
+        }
+    }
+}
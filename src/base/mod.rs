@@ -1,126 +1,490 @@
+pub mod header_vec;
 pub mod vec_ref;
 
-use header_slice::HeaderVec;
+use crate::error::TryMutError;
+use alloc::vec::Vec;
+use allocator_api2::alloc::{Allocator, Global};
+use core::mem::{self, ManuallyDrop};
+use core::ptr::{self, NonNull};
+use header_vec::HeaderVec;
 use vec_ref::{HeaderVecParts, VecMut, VecRef};
 
-pub trait Counter: Default + Clone {}
+/// The reference-counting state stored in the header of every shared vector.
+/// `Default` must produce the state of a freshly allocated vector before its first handle has
+/// been counted with `VecType::incr`.
+pub trait Counter: Default + Clone {
+    /// The number of handles that can access the contents.
+    fn strong_count(&self) -> usize;
+    /// The number of handles that keep the allocation alive without access to the contents.
+    fn weak_count(&self) -> usize {
+        0
+    }
+    /// Returns the state for a copy of the vector this counts, before its first handle has been
+    /// counted. Counters that carry data about the contents pass it on to the copy here.
+    fn for_copy(&self) -> Self {
+        Self::default()
+    }
+    /// Resets the state of a vector whose contents were taken out of it, so it can be shared
+    /// again. Like `for_copy`, this keeps any data about the contents.
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
 
-pub struct BaseRcVec<V: VecType, T> {
-    parts: HeaderVecParts<V::Counter, T>,
+/// A handle to a shared vector whose buffer was allocated by `A`.
+/// The allocator is stored in the header, so it is cloned whenever the buffer is reallocated or
+/// copied; it should be a cheap handle such as `Global`, `&Bump` or `Rc<Pool>`.
+pub struct BaseRcVec<V: VecType, T, A: Allocator + Clone = Global> {
+    parts: HeaderVecParts<V::Counter, T, A>,
 }
 
+// SAFETY: a handle may only be sent to or shared with another thread if every handle to the same
+// vector can safely touch the counter concurrently, which is what `V::Counter: Sync` promises.
+// The allocator is shared by every handle, and whichever one is dropped last uses it.
+unsafe impl<V: VecType, T: Send + Sync, A: Allocator + Clone + Send + Sync> Send
+    for BaseRcVec<V, T, A>
+where
+    V::Counter: Send + Sync,
+{
+}
+unsafe impl<V: VecType, T: Send + Sync, A: Allocator + Clone + Send + Sync> Sync
+    for BaseRcVec<V, T, A>
+where
+    V::Counter: Send + Sync,
+{
+}
+
+/// A reference-counting policy: decides how a handle of a given kind updates the shared
+/// `Counter` and what it is allowed to do with the vector.
+///
+/// Prefer implementing the safe `vec::policy::Policy` trait when restricting an existing policy
+/// is enough.
+///
+/// # Safety
+/// Implementors decide when a handle may read, mutate, drop, and deallocate the shared vector.
+/// `can_get_mut` must only return `true` when no other handle can observe the vector, and
+/// `decr` must only request the contents be dropped or the vector be deallocated once every
+/// handle that could access them is gone, and `try_take` must only take them under the same
+/// condition.
+/// If `Counter` is `Sync`, all of these operations must be safe to call concurrently from
+/// multiple threads.
 pub unsafe trait VecType {
     type Counter: Counter;
+    /// Increments the counter for a new handle cloned from an existing one.
     fn incr(counter: &Self::Counter);
-    fn decr(counter: &Self::Counter);
+    /// Decrements the counter for a handle that is being dropped.
+    /// Calls `drop_contents` if this was the last handle that could access the contents, then
+    /// returns whether the vector itself should be deallocated.
+    fn decr<F: FnOnce()>(counter: &Self::Counter, drop_contents: F) -> bool;
+    /// Whether the handle may take ownership of the contents without copying them.
     fn can_take(counter: &Self::Counter) -> bool;
+    /// Releases the handle like `decr` if `can_take` allows it, moving the contents out with
+    /// `take_contents` and returning whether the vector should be deallocated. Otherwise returns
+    /// `None` and leaves the counter untouched.
+    /// Counters that other threads can change must check and release in one atomic step, or
+    /// another handle could be released in between and leave the contents to neither.
+    fn try_take<F: FnOnce()>(counter: &Self::Counter, take_contents: F) -> Option<bool> {
+        if Self::can_take(counter) {
+            Some(Self::decr(counter, take_contents))
+        } else {
+            None
+        }
+    }
+    /// Whether the handle may read the contents.
     fn can_get_ref(counter: &Self::Counter) -> bool;
+    /// Whether the handle may mutate the vector in place. If not, mutation copies it first.
     fn can_get_mut(counter: &Self::Counter) -> bool;
-    fn should_drop_entire_vector(counter: &Self::Counter) -> bool;
-    fn should_drop_contents(counter: &Self::Counter) -> bool;
-    fn can_create(counter: &Self::Counter) -> bool;
+    /// Increments the counter for a new handle converted from a handle of another type, if
+    /// allowed. Returns whether the counter was incremented.
+    fn try_incr(counter: &Self::Counter) -> bool;
+    /// Called whenever a handle is given mutable access to the vector, for counters that track
+    /// changes.
+    fn on_mut(_counter: &Self::Counter) {}
 }
 
 impl<V: VecType, T> BaseRcVec<V, T> {
-    unsafe fn from_parts(parts: HeaderVecParts<V::Counter, T>) -> Self {
+    /// Returns a handle to an empty vector without allocating. It acts like the only handle to
+    /// a vector of its own, and allocates once it is first mutated.
+    /// Only available for `Global`, since the placeholder has nowhere to store an allocator.
+    pub const fn new() -> Self {
+        Self {
+            parts: HeaderVecParts::EMPTY,
+        }
+    }
+
+    /// Returns a handle that borrows `src` until it is first mutated, without allocating.
+    /// Only available for `Global` for the same reason as `new`.
+    pub const fn from_static(src: &'static &'static [T]) -> Self {
+        Self {
+            parts: HeaderVecParts::from_static(src),
+        }
+    }
+}
+
+/// Returns the allocator of a handle without a header.
+/// SAFETY: only call this for a placeholder or static data, which are only created for `Global`,
+/// so `A` is the zero-sized `Global` and any well-aligned pointer is valid to read it from.
+unsafe fn placeholder_alloc<'a, A>() -> &'a A {
+    debug_assert_eq!(mem::size_of::<A>(), 0);
+    &*NonNull::<A>::dangling().as_ptr()
+}
+
+impl<V: VecType, T, A: Allocator + Clone> BaseRcVec<V, T, A> {
+    unsafe fn from_parts(parts: HeaderVecParts<V::Counter, T, A>) -> Self {
         let this = Self { parts };
-        V::incr(this.counter());
+        if let Some(counter) = this.counter() {
+            V::incr(counter);
+        }
         this
     }
 
-    pub fn from_vec(mut src: HeaderVec<V::Counter, T>) -> Self {
-        src.head = Default::default();
+    pub fn from_vec(mut src: HeaderVec<V::Counter, T, A>) -> Self {
+        src.counter_mut().reset();
         unsafe { Self::from_parts(HeaderVecParts::from_vec(src)) }
     }
 
-    unsafe fn unsafe_vec_ref(&self) -> VecRef<V::Counter, T> {
+    /// Returns a copy of the allocator the vector lives in.
+    pub fn allocator(&self) -> &A {
+        if !self.parts.has_header() {
+            unsafe { placeholder_alloc() }
+        } else {
+            // SAFETY: the header outlives every handle, and the allocator is never written
+            unsafe { self.parts.alloc() }
+        }
+    }
+
+    unsafe fn unsafe_vec_ref(&self) -> VecRef<'_, T> {
         VecRef::new(&self.parts)
     }
 
-    unsafe fn unsafe_vec_mut(&mut self) -> VecMut<V::Counter, T> {
-        let vr = VecMut::new(&mut self.parts);
-        vr
+    /// SAFETY: Promise that no other handle can access the vector while the result exists.
+    pub unsafe fn unsafe_vec_mut(&mut self) -> VecMut<'_, V::Counter, T, A> {
+        debug_assert!(!self.parts.is_borrowed());
+        if self.parts.is_empty_placeholder() {
+            *self = Self::from_vec(HeaderVec::new_in(
+                Default::default(),
+                placeholder_alloc::<A>().clone(),
+            ));
+        }
+        V::on_mut(self.parts.counter());
+        VecMut::new(&mut self.parts)
     }
 
-    fn counter(&self) -> &V::Counter {
+    /// Returns `None` for the placeholders and static data, which have no header to hold one.
+    pub fn counter(&self) -> Option<&V::Counter> {
+        if !self.parts.has_header() {
+            return None;
+        }
         // SAFETY: at least the counter must exist if this instance exists
-        unsafe { VecRef::get_head(&self.unsafe_vec_ref()) }
+        Some(unsafe { self.parts.counter() })
+    }
+
+    /// Returns the counter for a copy of this vector.
+    pub fn counter_for_copy(&self) -> V::Counter {
+        self.counter()
+            .map_or_else(Default::default, Counter::for_copy)
+    }
+
+    pub fn strong_count(&self) -> usize {
+        if self.parts.is_dead_placeholder() {
+            return 0;
+        }
+        self.counter().map_or(1, Counter::strong_count)
     }
 
-    pub fn try_vec_ref(&self) -> Option<VecRef<V::Counter, T>> {
-        let vr = unsafe { self.unsafe_vec_ref() };
-        if V::can_get_ref(&vr.head) {
-            Some(vr)
+    pub fn weak_count(&self) -> usize {
+        self.counter().map_or(0, Counter::weak_count)
+    }
+
+    /// Returns a pointer to the first element without reading the length, so it is valid for
+    /// handles that can't access the contents.
+    pub fn as_ptr(&self) -> *const T {
+        self.parts.body_ptr()
+    }
+
+    pub fn ptr_eq<V2: VecType<Counter = V::Counter>>(&self, other: &BaseRcVec<V2, T, A>) -> bool {
+        self.parts.ptr == other.parts.ptr
+    }
+
+    pub fn try_vec_ref(&self) -> Option<VecRef<'_, T>> {
+        if self.counter().is_none_or(V::can_get_ref) {
+            Some(unsafe { self.unsafe_vec_ref() })
         } else {
             None
         }
     }
 
-    pub fn _try_vec_mut(&mut self) -> Option<VecMut<V::Counter, T>> {
-        if V::can_get_mut(self.counter()) {
+    /// Static data can't be mutated in place, so borrowed vectors are copied first even if this
+    /// is the only handle.
+    pub fn can_get_mut(&self) -> bool {
+        self.counter().is_none_or(V::can_get_mut) && !self.parts.is_borrowed()
+    }
+
+    pub fn _try_vec_mut(&mut self) -> Option<VecMut<'_, V::Counter, T, A>> {
+        if self.can_get_mut() {
             Some(unsafe { self.unsafe_vec_mut() })
         } else {
             None
         }
     }
 
-    pub fn try_convert<V2: VecType<Counter = V::Counter>>(&self) -> Option<BaseRcVec<V2, T>> {
-        if V2::can_create(self.counter()) {
-            Some(unsafe { BaseRcVec::from_parts(self.parts) })
+    /// Takes ownership of the vector if this is the only handle to it.
+    pub fn try_take_vec(self) -> Result<HeaderVec<V::Counter, T, A>, Self> {
+        if !self.can_get_mut() {
+            return Err(self);
+        }
+        if self.parts.is_empty_placeholder() {
+            return Ok(HeaderVec::with_capacity_in(
+                Default::default(),
+                0,
+                self.allocator().clone(),
+            ));
+        }
+
+        let vec = unsafe { self.parts.into_vec() };
+        // ownership of the vector has moved to `vec`, so this handle must not release it
+        mem::forget(self);
+        Ok(vec)
+    }
+
+    pub fn try_convert<V2: VecType<Counter = V::Counter>>(&self) -> Option<BaseRcVec<V2, T, A>> {
+        if self.parts.is_empty_placeholder() {
+            // there is no counter to tell when the handles to the placeholder are gone, so like
+            // `Weak::new`, the result stands for a vector that is already dead
+            return Some(BaseRcVec {
+                parts: HeaderVecParts::DEAD,
+            });
+        }
+        if self.parts.is_borrowed() {
+            // static data is never freed
+            return Some(BaseRcVec { parts: self.parts });
+        }
+        if self.counter().is_some_and(V2::try_incr) {
+            Some(BaseRcVec { parts: self.parts })
         } else {
             None
         }
     }
+
+    /// Releases this handle, moving the contents into a `Vec` instead of dropping them if this
+    /// was the last handle with access to them.
+    pub fn into_inner(self) -> Option<Vec<T>> {
+        let mut this = ManuallyDrop::new(self);
+        let mut inner = None;
+        this.release(Some(&mut inner));
+        inner
+    }
+
+    /// Releases this handle and returns the contents if it may take them, as decided by
+    /// `VecType::try_take`. Otherwise returns the handle unchanged.
+    pub fn try_into_inner(self) -> Result<Vec<T>, Self> {
+        let parts = self.parts;
+        let counter = match self.counter() {
+            Some(counter) => counter,
+            // static data belongs to no handle
+            None if parts.is_borrowed() => return Err(self),
+            None => return Ok(Vec::new()),
+        };
+        let mut inner = None;
+        // SAFETY: `try_take` only asks for the contents once no other handle can access them
+        let take_contents = || unsafe { Self::drop_parts_contents(parts, Some(&mut inner)) };
+        let dealloc = match V::try_take(counter, take_contents) {
+            Some(dealloc) => dealloc,
+            None => return Err(self),
+        };
+        mem::forget(self);
+        if dealloc {
+            unsafe { Self::dealloc_parts(parts) }
+        }
+        match inner {
+            Some(v) => Ok(v),
+            None => unreachable!("a handle that could take the contents didn't get them"),
+        }
+    }
+
+    /// Decrements the counter, moving the contents into `take` instead of dropping them if this
+    /// was the last handle with access to them, then deallocates the vector if needed.
+    fn release(&mut self, take: Option<&mut Option<Vec<T>>>) {
+        let parts = self.parts;
+        let counter = match self.counter() {
+            Some(counter) => counter,
+            None => {
+                // static data belongs to no handle, so it is left where it is
+                if let (Some(take), false) = (take, parts.is_borrowed()) {
+                    *take = Some(Vec::new());
+                }
+                return;
+            }
+        };
+        // SAFETY: `decr` only asks for the contents to be dropped once no other handle can
+        // access them
+        let drop_contents = || unsafe { Self::drop_parts_contents(parts, take) };
+
+        if V::decr(counter, drop_contents) {
+            unsafe { Self::dealloc_parts(parts) }
+        }
+    }
+
+    /// Drops the elements of a node in place, or moves them into `take`, leaving it empty.
+    /// SAFETY: `parts` must point to a live node whose contents no handle can access.
+    unsafe fn drop_parts_contents(
+        mut parts: HeaderVecParts<V::Counter, T, A>,
+        take: Option<&mut Option<Vec<T>>>,
+    ) {
+        let mut v = VecMut::new(&mut parts);
+        if let Some(take) = take {
+            *take = Some(move_into_vec(&mut v));
+        }
+        v.clear_in_place();
+    }
+
+    /// Deallocates a node without dropping its elements.
+    /// SAFETY: `parts` must point to a live node that is never touched again.
+    unsafe fn dealloc_parts(mut parts: HeaderVecParts<V::Counter, T, A>) {
+        VecMut::dealloc_vector(VecMut::new(&mut parts))
+    }
+
+    /// Returns the pointer identifying the vector, which `from_raw` turns back into a handle.
+    #[cfg(feature = "std")]
+    pub fn as_raw(&self) -> NonNull<u8> {
+        self.parts.ptr.cast()
+    }
+
+    /// Borrows the vector identified by `ptr` as a handle that was never counted, so it must not
+    /// be dropped.
+    /// SAFETY: `ptr` must come from `as_raw` on a handle of the same type, and the vector must not
+    /// have been deallocated.
+    #[cfg(feature = "std")]
+    pub unsafe fn from_raw(ptr: NonNull<u8>) -> ManuallyDrop<Self> {
+        ManuallyDrop::new(Self {
+            parts: HeaderVecParts { ptr: ptr.cast() },
+        })
+    }
+
+    /// Returns the elements without asking the counter whether they may be read.
+    /// SAFETY: the contents must not have been dropped, and nothing may mutate them while the
+    /// result exists.
+    #[cfg(feature = "std")]
+    pub unsafe fn contents_unchecked(&self) -> &[T] {
+        VecRef::get_body(self.unsafe_vec_ref())
+    }
+
+    /// Drops the elements in place without touching the counter.
+    /// SAFETY: no handle may access the contents while or after they are dropped.
+    #[cfg(feature = "std")]
+    pub unsafe fn drop_contents_unchecked(&self) {
+        Self::drop_parts_contents(self.parts, None)
+    }
+
+    /// Deallocates the vector without dropping its elements or touching the counter.
+    /// SAFETY: no handle to the vector may be used afterwards.
+    #[cfg(feature = "std")]
+    pub unsafe fn dealloc_unchecked(&self) {
+        Self::dealloc_parts(self.parts)
+    }
+}
+
+/// Moves every element of `src` into a new `Vec`, leaving `src` empty.
+fn move_into_vec<C, T, A: Allocator + Clone>(src: &mut HeaderVec<C, T, A>) -> Vec<T> {
+    move_tail_into_vec(src, 0)
+}
+
+/// Moves the elements of `src` from index `at` onwards into a new `Vec`, leaving the first `at`
+/// elements in place. Never shrinks the allocation.
+pub fn move_tail_into_vec<C, T, A: Allocator + Clone>(
+    src: &mut HeaderVec<C, T, A>,
+    at: usize,
+) -> Vec<T> {
+    let (ptr, len, cap) = src.as_raw_parts();
+    assert!(at <= len, "split index {} is out of bounds", at);
+    let mut dest = Vec::with_capacity(len - at);
+    unsafe {
+        ptr::copy_nonoverlapping(src.as_ptr().add(at), dest.as_mut_ptr(), len - at);
+        dest.set_len(len - at);
+        // the elements were moved, so forget them without dropping
+        ptr::write(src, HeaderVec::from_raw_parts(ptr, at, cap));
+    }
+    dest
 }
 
-impl<V: VecType, T: Clone> BaseRcVec<V, T> {
+impl<V: VecType, T: Clone, A: Allocator + Clone> BaseRcVec<V, T, A> {
+    /// The copy is allocated in the same allocator as the original.
     pub fn try_deep_clone(&self) -> Option<Self> {
-        let new_vec = self.try_vec_ref()?.clone();
+        let src = self.try_vec_ref()?;
+        let new_vec = HeaderVec::from_iter_in(
+            self.counter_for_copy(),
+            src.iter().cloned(),
+            self.allocator().clone(),
+        );
         Some(Self::from_vec(new_vec))
     }
 
-    fn try_make_unique(&mut self) -> bool {
-        if !V::can_get_mut(self.counter()) {
+    pub fn try_make_unique(&mut self) -> bool {
+        if !self.can_get_mut() {
             *self = match self.try_deep_clone() {
                 Some(x) => x,
                 None => return false,
             }
         }
-        return true;
+        true
     }
 
-    pub fn try_make_vec_mut(&mut self) -> Option<VecMut<V::Counter, T>> {
+    pub fn try_make_vec_mut(&mut self) -> Option<VecMut<'_, V::Counter, T, A>> {
         if !self.try_make_unique() {
             return None;
         }
         Some(unsafe { self.unsafe_vec_mut() })
     }
 
-    pub fn try_into_vec(mut self) -> Result<HeaderVec<V::Counter, T>, Self> {
+    pub fn try_into_vec(mut self) -> Result<HeaderVec<V::Counter, T, A>, Self> {
         if !self.try_make_unique() {
             return Err(self);
         }
+        self.try_take_vec()
+    }
 
-        Ok(unsafe { self.parts.into_vec() })
+    /// Like `try_deep_clone`, but reports allocation failure instead of panicking, and reserves
+    /// room for `additional` more elements in the copy.
+    fn checked_deep_clone(&self, additional: usize) -> Result<Self, TryMutError> {
+        let src = self.try_vec_ref().ok_or(TryMutError::NotUnique)?;
+        let cap = src
+            .len()
+            .checked_add(additional)
+            .ok_or(TryMutError::CapacityOverflow)?;
+        let mut new_vec = HeaderVec::try_with_capacity_in(
+            self.counter_for_copy(),
+            cap,
+            self.allocator().clone(),
+        )?;
+        // the capacity is already large enough, so this won't reallocate
+        new_vec.extend(src.iter().cloned());
+        Ok(Self::from_vec(new_vec))
+    }
+
+    /// Fallible counterpart to `try_make_vec_mut`. If the vector has to be copied, the copy has
+    /// room for `additional` more elements.
+    pub fn checked_make_vec_mut(
+        &mut self,
+        additional: usize,
+    ) -> Result<VecMut<'_, V::Counter, T, A>, TryMutError> {
+        if self.parts.is_empty_placeholder() {
+            let alloc = self.allocator().clone();
+            let new_vec = HeaderVec::try_with_capacity_in(Default::default(), additional, alloc)?;
+            *self = Self::from_vec(new_vec);
+        } else if !self.can_get_mut() {
+            *self = self.checked_deep_clone(additional)?;
+        }
+        Ok(unsafe { self.unsafe_vec_mut() })
     }
 }
 
-impl<V: VecType, T> Drop for BaseRcVec<V, T> {
+impl<V: VecType, T, A: Allocator + Clone> Drop for BaseRcVec<V, T, A> {
     fn drop(&mut self) {
-        unsafe {
-            V::decr(self.counter());
-
-            if V::should_drop_contents(self.counter()) {
-                self.unsafe_vec_mut().clear_in_place();
-            }
-            if V::should_drop_entire_vector(self.counter()) {
-                VecMut::dealloc_vector(self.unsafe_vec_mut())
-            }
-        }
+        self.release(None);
     }
 }
 
-impl<V: VecType, T> Clone for BaseRcVec<V, T> {
+impl<V: VecType, T, A: Allocator + Clone> Clone for BaseRcVec<V, T, A> {
     fn clone(&self) -> Self {
         unsafe { Self::from_parts(self.parts) }
     }
@@ -1,81 +1,190 @@
-use core::marker::PhantomData;
+use super::header_vec::{Header, HeaderVec, NodePtr};
+use allocator_api2::alloc::Allocator;
 use core::mem;
-use core::mem::MaybeUninit;
+use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::{Deref, DerefMut};
-use core::ptr::NonNull;
-use header_slice::pair::Pair;
-use header_slice::HeaderVec;
+use core::ptr::{self, NonNull};
+use core::slice;
+
+/// The address of the placeholder shared by every empty vector that hasn't needed an allocation
+/// yet. Like `alloc::rc::Weak::new`, this relies on no header ever being allocated there.
+const EMPTY_ADDR: usize = usize::MAX;
+
+/// The address of the placeholder that handles converted from `EMPTY` point to. There is no
+/// counter to tell whether the vector they came from is still alive, so like
+/// `alloc::rc::Weak::new`, they stand for one that is already gone.
+const DEAD_ADDR: usize = usize::MAX - 1;
+
+/// Set in a pointer to static data: the handle points to a `&'static [T]` in place of a header.
+/// Headers and references are both at least word-aligned, so this bit is otherwise clear.
+const BORROWED_TAG: usize = 1;
 
 #[derive(Debug)]
-pub struct HeaderVecParts<H, T> {
-    pub ptr: NonNull<Pair<H, MaybeUninit<T>>>,
-    pub len: usize,
-    pub cap: usize,
+pub struct HeaderVecParts<C, T, A> {
+    pub ptr: NodePtr<C, T, A>,
 }
 
-impl<H, T> HeaderVecParts<H, T> {
-    pub fn from_vec(src: HeaderVec<H, T>) -> Self {
+impl<C, T, A: Allocator + Clone> HeaderVecParts<C, T, A> {
+    /// Stands in for an empty vector without allocating. It is never dereferenced, and handles
+    /// pointing to it don't touch a counter.
+    pub const EMPTY: Self = Self {
+        ptr: unsafe { NonNull::new_unchecked(EMPTY_ADDR as *mut _) },
+    };
+
+    /// Stands in for a vector that is already gone. Like `EMPTY`, it is never dereferenced.
+    pub const DEAD: Self = Self {
+        ptr: unsafe { NonNull::new_unchecked(DEAD_ADDR as *mut _) },
+    };
+
+    pub fn from_vec(src: HeaderVec<C, T, A>) -> Self {
         let (ptr, len, cap) = src.into_raw_parts();
-        Self { ptr, len, cap }
+        let this = Self { ptr };
+        // SAFETY: `src` was just consumed, so nothing else references the header
+        unsafe { this.set_len_cap(len, cap) };
+        this
+    }
+
+    /// Points to `src` itself instead of a header, so nothing is allocated. There is no counter,
+    /// and the elements are never dropped.
+    pub const fn from_static(src: &'static &'static [T]) -> Self {
+        let tagged = (src as *const &[T]).cast::<u8>().wrapping_add(BORROWED_TAG);
+        Self {
+            // SAFETY: `src` is non-null, so it is still non-null after setting the low bit
+            ptr: unsafe { NonNull::new_unchecked(tagged as *mut _) },
+        }
+    }
+
+    fn addr(self) -> usize {
+        self.ptr.as_ptr() as *mut u8 as usize
+    }
+
+    /// Whether this is the `EMPTY` placeholder.
+    pub fn is_empty_placeholder(self) -> bool {
+        self.addr() == EMPTY_ADDR
+    }
+
+    /// Whether this is the `DEAD` placeholder.
+    pub fn is_dead_placeholder(self) -> bool {
+        self.addr() == DEAD_ADDR
+    }
+
+    /// Whether the elements are borrowed from static data, so the vector can't be mutated in
+    /// place.
+    pub fn is_borrowed(self) -> bool {
+        !self.is_empty_placeholder() && self.addr() & BORROWED_TAG != 0
+    }
+
+    /// Whether this points to a header, which holds the counter and allocator. Only the
+    /// placeholders and handles to static data have none.
+    pub fn has_header(self) -> bool {
+        !self.is_empty_placeholder() && !self.is_dead_placeholder() && !self.is_borrowed()
+    }
+
+    /// Returns the static data this borrows.
+    /// SAFETY: this must have been created by `from_static`.
+    unsafe fn borrowed<'a>(self) -> &'a [T] {
+        let src = (self.ptr.as_ptr() as *mut u8).wrapping_sub(BORROWED_TAG);
+        *(src as *const &'a [T])
+    }
+
+    /// Only valid for vectors that own their elements.
+    pub unsafe fn into_vec(self) -> HeaderVec<C, T, A> {
+        debug_assert!(self.has_header());
+        let head = self.head_ptr();
+        let len = ptr::read(ptr::addr_of!((*head).len));
+        let cap = ptr::read(ptr::addr_of!((*head).cap));
+        HeaderVec::from_raw_parts(self.ptr, len, cap)
     }
-    pub unsafe fn into_vec(self) -> HeaderVec<H, T> {
-        let Self { ptr, len, cap } = self;
-        HeaderVec::from_raw_parts(ptr, len, cap)
+
+    /// Returns the counter without touching the length or capacity, which may be concurrently
+    /// written by the handle that is dropping the contents.
+    pub unsafe fn counter<'a>(self) -> &'a C {
+        &*ptr::addr_of!((*self.head_ptr()).counter)
+    }
+
+    /// Returns the allocator, which is never written while any handle exists.
+    /// Only valid if `has_header`.
+    pub unsafe fn alloc<'a>(self) -> &'a A {
+        &*ptr::addr_of!((*self.head_ptr()).alloc)
+    }
+
+    pub fn body_ptr(self) -> *const T {
+        if self.is_borrowed() {
+            // SAFETY: checked above
+            unsafe { self.borrowed().as_ptr() }
+        } else if !self.has_header() {
+            NonNull::dangling().as_ptr()
+        } else {
+            unsafe { ptr::addr_of!((*self.ptr.as_ptr()).1) as *const T }
+        }
+    }
+
+    fn head_ptr(self) -> *mut Header<C, A> {
+        unsafe { ptr::addr_of_mut!((*self.ptr.as_ptr()).0) }
+    }
+
+    /// SAFETY: Promise that no other references to the length or capacity exist.
+    unsafe fn set_len_cap(self, len: usize, cap: usize) {
+        let head = self.head_ptr();
+        ptr::write(ptr::addr_of_mut!((*head).len), len);
+        ptr::write(ptr::addr_of_mut!((*head).cap), cap);
     }
 }
 
-impl<H, T> Clone for HeaderVecParts<H, T> {
+impl<C, T, A> Clone for HeaderVecParts<C, T, A> {
     fn clone(&self) -> Self {
-        Self {
-            ptr: self.ptr,
-            len: self.len,
-            cap: self.cap,
-        }
+        *self
     }
 }
-impl<H, T> Copy for HeaderVecParts<H, T> {}
+impl<C, T, A> Copy for HeaderVecParts<C, T, A> {}
 
-pub struct VecRef<'a, H: 'a, T: 'a> {
-    inner: MaybeUninit<HeaderVec<H, T>>,
-    // make sure this struct can't outlive the data it's borrowing
-    _lt: PhantomData<&'a ()>,
+/// Read access to the elements of a vector, wherever they are stored.
+pub struct VecRef<'a, T: 'a> {
+    body: &'a [T],
+    cap: usize,
 }
 
-pub struct VecMut<'a, H: 'a, T: 'a> {
-    src: &'a mut HeaderVecParts<H, T>,
-    inner: MaybeUninit<HeaderVec<H, T>>,
+pub struct VecMut<'a, C: 'a, T: 'a, A: Allocator + Clone + 'a> {
+    src: &'a mut HeaderVecParts<C, T, A>,
+    inner: MaybeUninit<HeaderVec<C, T, A>>,
 }
 
-impl<'a, H, T> VecRef<'a, H, T> {
+impl<'a, T> VecRef<'a, T> {
     /// SAFETY: Promise that no mutable references to the vector will be created while this instance
     /// exists.
-    pub unsafe fn new(src: &'a HeaderVecParts<H, T>) -> Self {
-        let inner = MaybeUninit::new(src.into_vec());
+    pub unsafe fn new<C, A: Allocator + Clone>(src: &'a HeaderVecParts<C, T, A>) -> Self {
+        if src.is_borrowed() {
+            let body = src.borrowed();
+            return Self {
+                body,
+                cap: body.len(),
+            };
+        }
+        if !src.has_header() {
+            return Self { body: &[], cap: 0 };
+        }
+        let inner = ManuallyDrop::new(src.into_vec());
+        // SAFETY: The contract when creating this struct promises that there are no mutable
+        // references to the vector and it will not be dropped for the lifetime 'a
         Self {
-            inner,
-            _lt: PhantomData,
+            body: slice::from_raw_parts(inner.as_ptr(), inner.len()),
+            cap: inner.capacity(),
         }
     }
 
     pub fn get_body(this: Self) -> &'a [T] {
-        // SAFETY: The contract when creating this struct promises that there are no mutable
-        // references to the vector and it will not be dropped for the lifetime 'a
-        let ptr = this.inner.as_ptr();
-        unsafe { &(*ptr).body }
+        this.body
     }
 
-    pub(super) fn get_head(this: &Self) -> &'a H {
-        // SAFETY: The contract when creating this struct promises that there are no mutable
-        // references to the vector and it will not be dropped for the lifetime 'a
-        let ptr = this.inner.as_ptr();
-        unsafe { &(*ptr).head }
+    pub fn capacity(&self) -> usize {
+        self.cap
     }
 }
 
-impl<'a, H, T> VecMut<'a, H, T> {
+impl<'a, C, T, A: Allocator + Clone> VecMut<'a, C, T, A> {
     /// SAFETY: Promise that no other references to the vector will be created while this instance
     /// exists.
-    pub unsafe fn new(src: &'a mut HeaderVecParts<H, T>) -> Self {
+    pub unsafe fn new(src: &'a mut HeaderVecParts<C, T, A>) -> Self {
         let inner = MaybeUninit::new(src.into_vec());
         VecMut { src, inner }
     }
@@ -83,7 +192,9 @@ impl<'a, H, T> VecMut<'a, H, T> {
     /// Deallocates the vector referenced by this struct without dropping its contents.
     /// Make sure the vector will not be reconstructed from parts ever again.
     pub unsafe fn dealloc_vector(this: Self) {
-        let inner = mem::transmute_copy::<_, HeaderVec<H, T>>(&this.inner);
+        let inner = mem::transmute_copy::<_, HeaderVec<C, T, A>>(&this.inner);
+        // the header is about to be freed, so don't write the length and capacity back into it
+        mem::forget(this);
         inner.dealloc_without_dropping();
     }
 
@@ -91,33 +202,35 @@ impl<'a, H, T> VecMut<'a, H, T> {
         // SAFETY: The contract when creating this struct promises that there are no other
         // references to the data and it will not be dropped for the lifetime 'a
         let ptr = this.inner.as_mut_ptr();
-        unsafe { &mut (*ptr).body }
+        unsafe { &mut *ptr }
     }
 }
 
-impl<'a, H, T> Drop for VecMut<'a, H, T> {
+impl<'a, C, T, A: Allocator + Clone> Drop for VecMut<'a, C, T, A> {
     fn drop(&mut self) {
         // Save any changes that were made
         let (ptr, len, cap) = self.as_raw_parts();
-        *self.src = HeaderVecParts { ptr, len, cap };
+        *self.src = HeaderVecParts { ptr };
+        // SAFETY: this instance has exclusive access to the vector
+        unsafe { self.src.set_len_cap(len, cap) };
     }
 }
 
-impl<'a, H, T> Deref for VecRef<'a, H, T> {
-    type Target = HeaderVec<H, T>;
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*self.inner.as_ptr() }
+impl<'a, T> Deref for VecRef<'a, T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.body
     }
 }
 
-impl<'a, H, T> Deref for VecMut<'a, H, T> {
-    type Target = HeaderVec<H, T>;
+impl<'a, C, T, A: Allocator + Clone> Deref for VecMut<'a, C, T, A> {
+    type Target = HeaderVec<C, T, A>;
     fn deref(&self) -> &Self::Target {
         unsafe { &*self.inner.as_ptr() }
     }
 }
 
-impl<'a, H, T> DerefMut for VecMut<'a, H, T> {
+impl<'a, C, T, A: Allocator + Clone> DerefMut for VecMut<'a, C, T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { &mut *self.inner.as_mut_ptr() }
     }
@@ -0,0 +1,143 @@
+use crate::base::VecType;
+use crate::vec::generic_vec::GenericVec;
+use allocator_api2::alloc::{Allocator, Global};
+use core::alloc::Layout;
+use core::fmt;
+use core::str::Utf8Error;
+
+/// The error returned when reserving capacity fails.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TryReserveError {
+    /// The requested capacity exceeds the maximum size of an allocation.
+    CapacityOverflow,
+    /// The allocator failed to provide memory for the given layout.
+    AllocError { layout: Layout },
+}
+
+/// The error returned when an operation requires the only handle to a vector but it is shared.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NotUnique;
+
+/// The error returned by the fallible mutation methods.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TryMutError {
+    /// The vector is shared and this handle is not allowed to copy it.
+    NotUnique,
+    /// The requested capacity exceeds the maximum size of an allocation.
+    CapacityOverflow,
+    /// The allocator failed to provide memory for the given layout.
+    AllocError { layout: Layout },
+    /// The index passed to an insertion is past the end of the vector.
+    IndexOutOfBounds { index: usize, len: usize },
+}
+
+/// The error returned when converting a byte vector that isn't valid UTF-8 into a string.
+/// Holds on to the original vector so it isn't lost.
+pub struct FromUtf8Error<V: VecType, A: Allocator + Clone = Global> {
+    pub(crate) bytes: GenericVec<V, u8, A>,
+    pub(crate) error: Utf8Error,
+}
+
+impl<V: VecType, A: Allocator + Clone> FromUtf8Error<V, A> {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the vector that failed to convert, without copying it.
+    pub fn into_bytes(self) -> GenericVec<V, u8, A> {
+        self.bytes
+    }
+
+    /// Details about where the invalid UTF-8 was found.
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+/// The error returned when converting invalid UTF-16 into a string.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FromUtf16Error(pub(crate) ());
+
+impl From<TryReserveError> for TryMutError {
+    fn from(src: TryReserveError) -> Self {
+        match src {
+            TryReserveError::CapacityOverflow => TryMutError::CapacityOverflow,
+            TryReserveError::AllocError { layout } => TryMutError::AllocError { layout },
+        }
+    }
+}
+
+impl TryMutError {
+    /// Converts the error from copying or growing a vector through a handle that is allowed to
+    /// copy it, which can only fail to allocate.
+    pub(crate) fn into_reserve_error(self) -> TryReserveError {
+        match self {
+            TryMutError::CapacityOverflow => TryReserveError::CapacityOverflow,
+            TryMutError::AllocError { layout } => TryReserveError::AllocError { layout },
+            TryMutError::NotUnique | TryMutError::IndexOutOfBounds { .. } => {
+                unreachable!("reserving can only fail to allocate")
+            }
+        }
+    }
+}
+
+impl fmt::Display for NotUnique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("vector is shared")
+    }
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryReserveError::AllocError { layout } => write!(
+                f,
+                "failed to allocate {} bytes with alignment {}",
+                layout.size(),
+                layout.align()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TryMutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryMutError::NotUnique => f.write_str("vector is shared and cannot be copied"),
+            TryMutError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryMutError::AllocError { layout } => write!(
+                f,
+                "failed to allocate {} bytes with alignment {}",
+                layout.size(),
+                layout.align()
+            ),
+            TryMutError::IndexOutOfBounds { index, len } => write!(
+                f,
+                "insertion index {} is out of bounds for length {}",
+                index, len
+            ),
+        }
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> fmt::Debug for FromUtf8Error<V, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FromUtf8Error")
+            .field("bytes", &self.bytes)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> fmt::Display for FromUtf8Error<V, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Display for FromUtf16Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid utf-16: lone surrogate found")
+    }
+}
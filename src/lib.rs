@@ -3,11 +3,17 @@
 #[allow(unused_imports)]
 #[macro_use]
 extern crate alloc;
-extern crate header_slice;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
 
 mod base;
+pub mod error;
 mod macros;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod string;
 #[cfg(test)]
 pub mod test;
 pub mod vec;
+
+pub use allocator_api2;
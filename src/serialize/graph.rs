@@ -0,0 +1,278 @@
+use super::DeserializePlain;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::any::{type_name, Any};
+use core::cell::RefCell;
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, VariantAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use serde::Deserialize;
+
+/// Serializes or deserializes the wrapped value so that every buffer shared by several handles
+/// is only written once. The first handle to each buffer is written as `Def(contents)` and any
+/// later handles to it as `Ref(index)`, where `index` counts `Def`s in the order they were
+/// written. Deserializing the output restores the sharing.
+///
+/// Only handles reached while serializing the wrapped value take part, and a weak handle to a
+/// buffer that is still being deserialized (i.e. a cycle) fails to deserialize. Empty vectors
+/// that haven't allocated yet are always written as a `Def`.
+///
+/// Sharing is only restored for handles of `'static` types, since back-references are checked
+/// against the type they were written as.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedGraph<T>(pub T);
+
+struct SerState {
+    ids: BTreeMap<(*const u8, &'static str), u64>,
+    /// A clone of every handle that was written as a `Def`, so its buffer can't be freed and its
+    /// address reused by another buffer before the session ends.
+    kept: Vec<Kept>,
+}
+
+/// A type-erased handle owned by a `SerState`.
+struct Kept {
+    handle: *mut (),
+    drop_handle: unsafe fn(*mut ()),
+}
+
+/// A handle kept alive so later `Ref`s to it can be resolved.
+struct Entry {
+    // `None` while the handle is still being deserialized
+    handle: Option<Box<dyn Any>>,
+    type_name: &'static str,
+}
+
+struct DeState {
+    entries: Vec<Entry>,
+}
+
+std::thread_local! {
+    static SER_STATE: RefCell<Option<SerState>> = const { RefCell::new(None) };
+    static DE_STATE: RefCell<Option<DeState>> = const { RefCell::new(None) };
+}
+
+unsafe fn drop_handle<H>(handle: *mut ()) {
+    drop(Box::from_raw(handle as *mut H));
+}
+
+impl Drop for SerState {
+    fn drop(&mut self) {
+        for k in &self.kept {
+            // SAFETY: `drop_handle` was chosen for the type that `handle` points to, and the
+            // session ends before anything the handle borrows
+            unsafe { (k.drop_handle)(k.handle) };
+        }
+    }
+}
+
+/// Restores the state of an enclosing `SharedGraph`, if any, when this one ends.
+struct Session<S: 'static> {
+    key: &'static std::thread::LocalKey<RefCell<Option<S>>>,
+    prev: Option<S>,
+}
+
+impl<S> Session<S> {
+    fn begin(key: &'static std::thread::LocalKey<RefCell<Option<S>>>, state: S) -> Self {
+        let prev = key.with(|st| st.borrow_mut().replace(state));
+        Self { key, prev }
+    }
+}
+
+impl<S> Drop for Session<S> {
+    fn drop(&mut self) {
+        let prev = self.prev.take();
+        // drop the finished state after releasing the borrow, since dropping handles may run
+        // arbitrary code
+        let finished = self
+            .key
+            .with(|st| mem::replace(&mut *st.borrow_mut(), prev));
+        drop(finished);
+    }
+}
+
+pub(super) fn serializing() -> bool {
+    SER_STATE.with(|st| st.borrow().is_some())
+}
+
+pub(super) fn deserializing() -> bool {
+    DE_STATE.with(|st| st.borrow().is_some())
+}
+
+/// Writes `handle` as a `Def` the first time the buffer identified by `id` is seen, and as a
+/// `Ref` after that. Handles without an `id` are always written as a `Def`.
+pub(super) fn serialize_shared<H: Clone, C: Serialize + ?Sized, S: Serializer>(
+    handle: &H,
+    id: Option<*const u8>,
+    contents: &C,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    // a name collision at worst writes a `Ref` that fails to deserialize
+    let key = id.map(|ptr| (ptr, type_name::<H>()));
+    let existing = SER_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        let st = st.as_mut().unwrap();
+        // every `Def` is kept, so this is its index
+        let id = st.kept.len() as u64;
+        if let Some(key) = key {
+            if let Some(&id) = st.ids.get(&key) {
+                return Some(id);
+            }
+            st.ids.insert(key, id);
+        }
+        st.kept.push(Kept {
+            handle: Box::into_raw(Box::new(handle.clone())) as *mut (),
+            drop_handle: drop_handle::<H>,
+        });
+        None
+    });
+
+    match existing {
+        Some(id) => s.serialize_newtype_variant("Shared", 1, "Ref", &id),
+        None => s.serialize_newtype_variant("Shared", 0, "Def", contents),
+    }
+}
+
+pub(super) fn deserialize_shared<'de, H, D>(d: D) -> Result<H, D::Error>
+where
+    H: DeserializePlain<'de> + Clone + 'static,
+    D: Deserializer<'de>,
+{
+    d.deserialize_enum("Shared", &["Def", "Ref"], SharedVisitor(PhantomData))
+}
+
+fn reserve_entry<H>() -> usize {
+    DE_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        let entries = &mut st.as_mut().unwrap().entries;
+        entries.push(Entry {
+            handle: None,
+            type_name: type_name::<H>(),
+        });
+        entries.len() - 1
+    })
+}
+
+fn fill_entry<H: 'static>(index: usize, handle: H) {
+    DE_STATE.with(|st| {
+        let mut st = st.borrow_mut();
+        st.as_mut().unwrap().entries[index].handle = Some(Box::new(handle));
+    })
+}
+
+fn lookup_entry<H: Clone + 'static, E: de::Error>(id: u64) -> Result<H, E> {
+    DE_STATE.with(|st| {
+        let st = st.borrow();
+        let e = usize::try_from(id)
+            .ok()
+            .and_then(|i| st.as_ref().unwrap().entries.get(i))
+            .ok_or_else(|| E::custom(format_args!("back-reference {} is out of range", id)))?;
+        let handle = e.handle.as_ref().ok_or_else(|| {
+            E::custom(format_args!(
+                "back-reference {} is to a value that is still being deserialized",
+                id
+            ))
+        })?;
+        let handle = handle.downcast_ref::<H>().ok_or_else(|| {
+            E::custom(format_args!(
+                "back-reference {} is a {}, not a {}",
+                id,
+                e.type_name,
+                type_name::<H>()
+            ))
+        })?;
+        Ok(handle.clone())
+    })
+}
+
+struct SharedVisitor<H>(PhantomData<fn() -> H>);
+
+impl<'de, H: DeserializePlain<'de> + Clone + 'static> Visitor<'de> for SharedVisitor<H> {
+    type Value = H;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a shared definition or back-reference")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<H, A::Error> {
+        match data.variant()? {
+            (Tag::Def, access) => {
+                let index = reserve_entry::<H>();
+                let handle = access.newtype_variant_seed(PlainSeed::<H>(PhantomData))?;
+                fill_entry(index, handle.clone());
+                Ok(handle)
+            }
+            (Tag::Ref, access) => lookup_entry(access.newtype_variant()?),
+        }
+    }
+}
+
+struct PlainSeed<H>(PhantomData<fn() -> H>);
+
+impl<'de, H: DeserializePlain<'de>> DeserializeSeed<'de> for PlainSeed<H> {
+    type Value = H;
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<H, D::Error> {
+        H::deserialize_plain(d)
+    }
+}
+
+enum Tag {
+    Def,
+    Ref,
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_identifier(TagVisitor)
+    }
+}
+
+struct TagVisitor;
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("`Def` or `Ref`")
+    }
+
+    fn visit_u64<E: de::Error>(self, i: u64) -> Result<Tag, E> {
+        match i {
+            0 => Ok(Tag::Def),
+            1 => Ok(Tag::Ref),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(i), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Tag, E> {
+        match s {
+            "Def" => Ok(Tag::Def),
+            "Ref" => Ok(Tag::Ref),
+            _ => Err(E::unknown_variant(s, &["Def", "Ref"])),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for SharedGraph<T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let state = SerState {
+            ids: BTreeMap::new(),
+            kept: Vec::new(),
+        };
+        let _session = Session::begin(&SER_STATE, state);
+        self.0.serialize(s)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SharedGraph<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let state = DeState {
+            entries: Vec::new(),
+        };
+        let _session = Session::begin(&DE_STATE, state);
+        T::deserialize(d).map(SharedGraph)
+    }
+}
@@ -0,0 +1,197 @@
+//! `serde` support, enabled by the `serde` feature.
+//!
+//! Each handle is serialized as a plain copy of its contents, so handles that shared a buffer
+//! before serialization get separate buffers after deserialization. Wrap the value in
+//! `SharedGraph` to preserve the sharing instead.
+//!
+//! Deserializing a handle requires its element type to be `'static`.
+
+#[cfg(feature = "std")]
+mod graph;
+
+#[cfg(feature = "std")]
+pub use graph::SharedGraph;
+
+use crate::base::VecType;
+use crate::string::weak::{ArcWeakString, RcWeakString};
+use crate::string::{GenericString, StrongArcString, StrongRcString};
+use crate::vec::generic_vec::GenericVec;
+use crate::vec::strong_weak::{ArcWeakVec, WeakVec};
+use crate::vec::unique::UniqueVec;
+use crate::vec::{ArcVec, RcVec};
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Deserializes a handle's contents into a new buffer, bypassing the shared graph.
+trait DeserializePlain<'de>: Sized {
+    fn deserialize_plain<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error>;
+}
+
+impl<V: VecType, T: Serialize> Serialize for GenericVec<V, T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "std")]
+        {
+            if graph::serializing() {
+                return graph::serialize_shared(self, self.shared_id(), &**self, s);
+            }
+        }
+        s.collect_seq(self.iter())
+    }
+}
+
+impl<'de, V: VecType + 'static, T: Deserialize<'de> + 'static> Deserialize<'de>
+    for GenericVec<V, T>
+{
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[cfg(feature = "std")]
+        {
+            if graph::deserializing() {
+                return graph::deserialize_shared(d);
+            }
+        }
+        Self::deserialize_plain(d)
+    }
+}
+
+impl<'de, V: VecType, T: Deserialize<'de>> DeserializePlain<'de> for GenericVec<V, T> {
+    fn deserialize_plain<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_seq(VecVisitor(PhantomData))
+    }
+}
+
+struct VecVisitor<V: VecType, T>(PhantomData<fn() -> GenericVec<V, T>>);
+
+impl<'de, V: VecType, T: Deserialize<'de>> Visitor<'de> for VecVisitor<V, T> {
+    type Value = GenericVec<V, T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        // don't trust the size hint too much, since it comes from the input
+        let cap = seq.size_hint().unwrap_or(0).min(4096);
+        let mut v = UniqueVec::with_capacity(cap);
+        while let Some(x) = seq.next_element()? {
+            v.push(x);
+        }
+        Ok(v.freeze())
+    }
+}
+
+impl<V: VecType> Serialize for GenericString<V> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "std")]
+        {
+            if graph::serializing() {
+                return graph::serialize_shared(self, self.shared_id(), self.as_str(), s);
+            }
+        }
+        s.serialize_str(self)
+    }
+}
+
+impl<'de, V: VecType + 'static> Deserialize<'de> for GenericString<V> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[cfg(feature = "std")]
+        {
+            if graph::deserializing() {
+                return graph::deserialize_shared(d);
+            }
+        }
+        Self::deserialize_plain(d)
+    }
+}
+
+impl<'de, V: VecType> DeserializePlain<'de> for GenericString<V> {
+    fn deserialize_plain<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_str(StringVisitor(PhantomData))
+    }
+}
+
+struct StringVisitor<V: VecType>(PhantomData<fn() -> GenericString<V>>);
+
+impl<'de, V: VecType> Visitor<'de> for StringVisitor<V> {
+    type Value = GenericString<V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        Ok(GenericString::from(s))
+    }
+}
+
+/// Serializes a weak handle as the upgraded value, or `None` if the value was dropped.
+fn serialize_weak<U: Serialize, S: Serializer>(
+    upgraded: Option<U>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match upgraded {
+        Some(x) => s.serialize_some(&x),
+        None => s.serialize_none(),
+    }
+}
+
+impl<T: Serialize> Serialize for WeakVec<T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_weak(self.upgrade(), s)
+    }
+}
+
+/// Nothing keeps the deserialized value alive unless it is part of a `SharedGraph` that also
+/// contains a strong handle to it, so otherwise the weak handle can't be upgraded.
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for WeakVec<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let strong = Option::<RcVec<T>>::deserialize(d)?.unwrap_or_default();
+        Ok(strong.downgrade())
+    }
+}
+
+impl<T: Serialize> Serialize for ArcWeakVec<T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_weak(self.upgrade(), s)
+    }
+}
+
+/// Like `WeakVec`, this can only be upgraded if it was deserialized as part of a `SharedGraph`
+/// that also contains a strong handle to the same value.
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for ArcWeakVec<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let strong = Option::<ArcVec<T>>::deserialize(d)?.unwrap_or_default();
+        Ok(strong.downgrade())
+    }
+}
+
+impl Serialize for RcWeakString {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_weak(self.upgrade(), s)
+    }
+}
+
+/// Like `WeakVec`, this can only be upgraded if it was deserialized as part of a `SharedGraph`
+/// that also contains a strong handle to the same value.
+impl<'de> Deserialize<'de> for RcWeakString {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let strong = Option::<StrongRcString>::deserialize(d)?.unwrap_or_default();
+        Ok(strong.downgrade())
+    }
+}
+
+impl Serialize for ArcWeakString {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_weak(self.upgrade(), s)
+    }
+}
+
+/// Like `WeakVec`, this can only be upgraded if it was deserialized as part of a `SharedGraph`
+/// that also contains a strong handle to the same value.
+impl<'de> Deserialize<'de> for ArcWeakString {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let strong = Option::<StrongArcString>::deserialize(d)?.unwrap_or_default();
+        Ok(strong.downgrade())
+    }
+}
@@ -1,25 +1,130 @@
-use crate::vec::AcycVec;
+use crate::base::VecType;
+use crate::error::{FromUtf16Error, FromUtf8Error};
+use crate::vec::counters::{AcycType, AtomicAcycType, AtomicStrongType, StrongType};
+use crate::vec::generic_vec::GenericVec;
+use crate::vec::slice::resolve_range;
+use crate::vec::unique::UniqueVec;
+use alloc::borrow::Cow;
 use alloc::string::String;
+use alloc::vec;
+use allocator_api2::alloc::{Allocator, Global};
+use core::borrow::Borrow;
 use core::cmp::Ordering;
 use core::fmt;
-use core::iter;
+use core::hash::{Hash, Hasher};
+use core::iter::{self, FusedIterator};
 use core::ops::Index;
 use core::ops::{Add, AddAssign};
-use core::ops::{Deref, DerefMut};
+use core::ops::{Deref, DerefMut, RangeBounds};
 use core::slice::SliceIndex;
 
-#[derive(Clone)]
-pub struct RcString {
-    base: AcycVec<u8>,
+pub mod interner;
+pub mod rope;
+pub mod small;
+pub mod substr;
+pub mod weak;
+
+/// A shared string whose buffer is allocated by `A`.
+pub struct GenericString<V: VecType, A: Allocator + Clone = Global> {
+    base: GenericVec<V, u8, A>,
 }
 
-impl RcString {
-    pub fn new() -> Self {
+pub type RcString<A = Global> = GenericString<AcycType, A>;
+pub type ArcString<A = Global> = GenericString<AtomicAcycType, A>;
+/// A string that supports weak handles, unlike `RcString`.
+pub type StrongRcString<A = Global> = GenericString<StrongType, A>;
+/// A thread-safe string that supports weak handles, unlike `ArcString`.
+pub type StrongArcString<A = Global> = GenericString<AtomicStrongType, A>;
+pub type RcStr = substr::GenericStr<AcycType>;
+pub type ArcStr = substr::GenericStr<AtomicAcycType>;
+pub type RcRope = rope::GenericRope<AcycType>;
+pub type ArcRope = rope::GenericRope<AtomicAcycType>;
+pub type SmallRcString = small::GenericSmallString<AcycType>;
+pub type SmallArcString = small::GenericSmallString<AtomicAcycType>;
+pub type Interner = interner::GenericInterner<AcycType>;
+/// An interner whose handles can be sent to other threads. It can be shared between threads
+/// behind a lock.
+pub type ArcInterner = interner::GenericInterner<AtomicAcycType>;
+
+impl<V: VecType> GenericString<V> {
+    /// Doesn't allocate until the string is first mutated, and can be used in constant expressions.
+    pub const fn new() -> Self {
+        Self {
+            base: GenericVec::new(),
+        }
+    }
+
+    /// Wraps `src` without copying it or allocating, as `GenericVec::from_static` does. The
+    /// contents are copied on the first mutation.
+    pub const fn from_static(src: &'static &'static str) -> Self {
+        // SAFETY: `str` has the same layout as `[u8]`, so references to them do too
+        let bytes = unsafe { &*(src as *const &'static str as *const &'static [u8]) };
         Self {
-            base: AcycVec::new(),
+            base: GenericVec::from_static(bytes),
         }
     }
 
+    /// Decodes UTF-16 into a new string, failing if it contains lone surrogates.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, FromUtf16Error> {
+        let mut this = Self::with_capacity(v.len());
+        for c in char::decode_utf16(v.iter().copied()) {
+            this.push(c.map_err(|_| FromUtf16Error(()))?);
+        }
+        Ok(this)
+    }
+
+    /// Decodes UTF-16 into a new string, replacing lone surrogates with `U+FFFD`.
+    pub fn from_utf16_lossy(v: &[u16]) -> Self {
+        char::decode_utf16(v.iter().copied())
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            base: GenericVec::with_capacity(cap),
+        }
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> GenericString<V, A> {
+    /// Unlike `new`, this allocates a header to hold `alloc`, but no room for the contents.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            base: GenericVec::new_in(alloc),
+        }
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Self {
+            base: GenericVec::with_capacity_in(cap, alloc),
+        }
+    }
+
+    /// Copies `src` into a new string allocated in `alloc`.
+    pub fn from_str_in(src: &str, alloc: A) -> Self {
+        Self {
+            base: GenericVec::copy_from_slice_in(src.as_bytes(), alloc),
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.base.allocator()
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.base.is_unique()
+    }
+
+    /// Whether both handles point to the same string.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+
     pub fn push(&mut self, c: char) {
         match c.len_utf8() {
             1 => self.base.push(c as u8),
@@ -30,48 +135,325 @@ impl RcString {
     pub fn push_str<S: AsRef<str>>(&mut self, s: S) {
         self.base.extend_from_slice(s.as_ref().as_bytes());
     }
+
+    /// Converts a byte vector into a string without copying it, if it is valid UTF-8.
+    /// Otherwise, the error holds on to the original vector.
+    pub fn from_utf8(bytes: GenericVec<V, u8, A>) -> Result<Self, FromUtf8Error<V, A>> {
+        match core::str::from_utf8(&bytes) {
+            Ok(_) => Ok(Self { base: bytes }),
+            Err(error) => Err(FromUtf8Error { bytes, error }),
+        }
+    }
+
+    /// Converts a byte vector into a string without copying it or checking that it is valid
+    /// UTF-8.
+    ///
+    /// # Safety
+    /// `bytes` must be valid UTF-8. Every handle to the vector may be turned into a string this
+    /// way, so it must stay valid UTF-8 as long as any of them exist.
+    pub unsafe fn from_utf8_unchecked(bytes: GenericVec<V, u8, A>) -> Self {
+        Self { base: bytes }
+    }
+
+    /// Converts a byte vector into a string, replacing invalid sequences with `U+FFFD`.
+    /// The vector is only copied if it isn't already valid UTF-8.
+    pub fn from_utf8_lossy(bytes: GenericVec<V, u8, A>) -> Self {
+        match String::from_utf8_lossy(&bytes) {
+            Cow::Borrowed(_) => Self { base: bytes },
+            Cow::Owned(s) => Self::from_str_in(&s, bytes.allocator().clone()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.base.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more bytes, copying the string if it is
+    /// shared.
+    pub fn reserve(&mut self, additional: usize) {
+        self.base.reserve(additional);
+    }
+
+    /// Reserves capacity for exactly `additional` more bytes, copying the string if it is
+    /// shared.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.base.reserve_exact(additional);
+    }
+
+    /// Frees any unused capacity. Does nothing if the string is shared.
+    pub fn shrink_to_fit(&mut self) {
+        self.base.shrink_to_fit();
+    }
+
+    pub fn as_str(&self) -> &str {
+        self
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.base
+    }
+
+    #[cfg(all(feature = "serde", feature = "std"))]
+    pub(crate) fn shared_id(&self) -> Option<*const u8> {
+        self.base.shared_id()
+    }
+
+    /// Converts this into a handle to the underlying bytes without copying.
+    pub fn into_bytes(self) -> GenericVec<V, u8, A> {
+        self.base
+    }
+
+    pub fn clear(&mut self) {
+        self.base.clear();
+    }
+
+    /// Shortens the string to `new_len` bytes. Does nothing if it is already shorter.
+    /// Panics if `new_len` isn't on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(
+                self.is_char_boundary(new_len),
+                "new_len is not on a char boundary"
+            );
+            self.base.truncate(new_len);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        self.base.truncate(self.len() - c.len_utf8());
+        Some(c)
+    }
+
+    /// Removes the char starting at byte `index`.
+    /// Panics if `index` isn't on a char boundary or is at the end of the string.
+    pub fn remove(&mut self, index: usize) -> char {
+        let c = match self[index..].chars().next() {
+            Some(c) => c,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+        self.remove_bytes(index, index + c.len_utf8());
+        c
+    }
+
+    /// Inserts `c` at byte `index`. Panics if `index` isn't on a char boundary.
+    pub fn insert(&mut self, index: usize, c: char) {
+        self.insert_str(index, c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Inserts `s` at byte `index`. Panics if `index` isn't on a char boundary.
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        assert!(
+            self.is_char_boundary(index),
+            "index is not on a char boundary"
+        );
+        self.base.splice(index..index, s.bytes());
+    }
+
+    /// Keeps only the chars for which `f` returns `true`.
+    /// If the string is shared, only the kept chars are copied.
+    pub fn retain(&mut self, mut f: impl FnMut(char) -> bool) {
+        if !self.is_unique() {
+            let mut kept = Self::new_in(self.allocator().clone());
+            kept.extend(self.chars().filter(|&c| f(c)));
+            *self = kept;
+            return;
+        }
+
+        let mut gap = RetainGap {
+            vec: self.base.make_mut(),
+            read: 0,
+            write: 0,
+        };
+        while gap.read < gap.vec.len() {
+            // SAFETY: `read` always starts a char, and everything after it is untouched
+            let c = unsafe { core::str::from_utf8_unchecked(&gap.vec[gap.read..]) }
+                .chars()
+                .next()
+                .unwrap();
+            let (read, write, n) = (gap.read, gap.write, c.len_utf8());
+            if f(c) {
+                gap.vec.copy_within(read..read + n, write);
+                gap.write += n;
+            }
+            gap.read += n;
+        }
+    }
+
+    /// Removes the chars in the byte `range` and returns them in order.
+    /// Panics if the range is out of bounds or doesn't fall on char boundaries.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain {
+        let (start, end) = self.char_range(range);
+        Drain {
+            bytes: self.base.drain(start..end),
+        }
+    }
+
+    /// Replaces the byte `range` with `replace_with`.
+    /// Panics if the range is out of bounds or doesn't fall on char boundaries.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &str) {
+        let (start, end) = self.char_range(range);
+        self.base.splice(start..end, replace_with.bytes());
+    }
+
+    /// Moves the bytes from `at` onwards into a new string.
+    /// Panics if `at` isn't on a char boundary.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(self.is_char_boundary(at), "at is not on a char boundary");
+        Self {
+            base: self.base.split_off(at),
+        }
+    }
+
+    /// Converts the string to ASCII upper case in place, only copying it if it is shared and
+    /// contains lower case letters.
+    pub fn make_ascii_uppercase(&mut self) {
+        if self.bytes().any(|b| b.is_ascii_lowercase()) {
+            self.as_mut().make_ascii_uppercase();
+        }
+    }
+
+    /// Converts the string to ASCII lower case in place, only copying it if it is shared and
+    /// contains upper case letters.
+    pub fn make_ascii_lowercase(&mut self) {
+        if self.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.as_mut().make_ascii_lowercase();
+        }
+    }
+
+    fn char_range<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let (start, end) = resolve_range(range, self.len());
+        assert!(
+            self.is_char_boundary(start) && self.is_char_boundary(end),
+            "byte range {}..{} does not fall on char boundaries",
+            start,
+            end
+        );
+        (start, end)
+    }
+
+    fn remove_bytes(&mut self, start: usize, end: usize) {
+        let v = self.base.make_mut();
+        v[start..].rotate_left(end - start);
+        let len = v.len();
+        v.truncate(len - (end - start));
+    }
+}
+
+/// Closes the gap between the kept and unvisited bytes when `retain` finishes or `f` panics,
+/// so the string is valid UTF-8 either way.
+struct RetainGap<'a, V: VecType, A: Allocator + Clone> {
+    vec: &'a mut UniqueVec<V, u8, A>,
+    read: usize,
+    write: usize,
+}
+
+impl<'a, V: VecType, A: Allocator + Clone> Drop for RetainGap<'a, V, A> {
+    fn drop(&mut self) {
+        let gap = self.read - self.write;
+        self.vec[self.write..].rotate_left(gap);
+        let len = self.vec.len();
+        self.vec.truncate(len - gap);
+    }
+}
+
+/// The chars removed from a string by `drain`.
+pub struct Drain {
+    bytes: vec::IntoIter<u8>,
+}
+
+impl Drain {
+    /// The chars that haven't been yielded yet.
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes were drained from char boundaries, and only whole chars are taken
+        unsafe { core::str::from_utf8_unchecked(self.bytes.as_slice()) }
+    }
+}
+
+impl Iterator for Drain {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next()?;
+        self.bytes.nth(c.len_utf8() - 1);
+        Some(c)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bytes.len();
+        (len.div_ceil(4), Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Drain {
+    fn next_back(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.bytes.nth_back(c.len_utf8() - 1);
+        Some(c)
+    }
+}
+
+impl FusedIterator for Drain {}
+
+impl fmt::Debug for Drain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_str()).finish()
+    }
 }
 
-impl From<&str> for RcString {
+impl<V: VecType, A: Allocator + Clone> Clone for GenericString<V, A> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+        }
+    }
+}
+
+impl<V: VecType> Default for GenericString<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType> From<&str> for GenericString<V> {
     fn from(src: &str) -> Self {
         Self {
-            base: AcycVec::copy_from_slice(src.as_bytes()),
+            base: GenericVec::copy_from_slice(src.as_bytes()),
         }
     }
 }
 
-impl AsRef<str> for RcString {
+impl<V: VecType, A: Allocator + Clone> AsRef<str> for GenericString<V, A> {
     fn as_ref(&self) -> &str {
         unsafe { core::str::from_utf8_unchecked(&self.base) }
     }
 }
 
-impl AsMut<str> for RcString {
+impl<V: VecType, A: Allocator + Clone> AsMut<str> for GenericString<V, A> {
     fn as_mut(&mut self) -> &mut str {
         unsafe { core::str::from_utf8_unchecked_mut(&mut self.base) }
     }
 }
 
-impl Deref for RcString {
+impl<V: VecType, A: Allocator + Clone> Deref for GenericString<V, A> {
     type Target = str;
     fn deref(&self) -> &str {
         self.as_ref()
     }
 }
 
-impl DerefMut for RcString {
+impl<V: VecType, A: Allocator + Clone> DerefMut for GenericString<V, A> {
     fn deref_mut(&mut self) -> &mut str {
         self.as_mut()
     }
 }
 
-impl<S: AsRef<str>> AddAssign<S> for RcString {
+impl<S: AsRef<str>, V: VecType, A: Allocator + Clone> AddAssign<S> for GenericString<V, A> {
     fn add_assign(&mut self, rhs: S) {
         self.push_str(rhs.as_ref());
     }
 }
 
-impl<S: AsRef<str>> Add<S> for RcString {
+impl<S: AsRef<str>, V: VecType, A: Allocator + Clone> Add<S> for GenericString<V, A> {
     type Output = Self;
     fn add(mut self, rhs: S) -> Self {
         self += rhs;
@@ -79,53 +461,124 @@ impl<S: AsRef<str>> Add<S> for RcString {
     }
 }
 
-impl<I: SliceIndex<str>> Index<I> for RcString {
+impl<I: SliceIndex<str>, V: VecType, A: Allocator + Clone> Index<I> for GenericString<V, A> {
     type Output = I::Output;
     fn index(&self, i: I) -> &Self::Output {
         str::index(self, i)
     }
 }
 
-impl<S: AsRef<str>> PartialEq<S> for RcString {
+/// Whether both strings are the same slice of memory, in which case they must be equal.
+fn same_str(a: &str, b: &str) -> bool {
+    a.as_ptr() == b.as_ptr() && a.len() == b.len()
+}
+
+impl<S: AsRef<str> + ?Sized, V: VecType, A: Allocator + Clone> PartialEq<S>
+    for GenericString<V, A>
+{
     fn eq(&self, rhs: &S) -> bool {
-        str::eq(self, rhs.as_ref())
+        let rhs = rhs.as_ref();
+        same_str(self, rhs) || str::eq(self, rhs)
     }
 }
 
-impl Eq for RcString {}
+impl<V: VecType, A: Allocator + Clone> Eq for GenericString<V, A> {}
 
-impl<S: AsRef<str>> PartialOrd<S> for RcString {
+impl<S: AsRef<str> + ?Sized, V: VecType, A: Allocator + Clone> PartialOrd<S>
+    for GenericString<V, A>
+{
     fn partial_cmp(&self, rhs: &S) -> Option<Ordering> {
-        str::partial_cmp(self, rhs.as_ref())
+        let rhs = rhs.as_ref();
+        if same_str(self, rhs) {
+            return Some(Ordering::Equal);
+        }
+        str::partial_cmp(self, rhs)
     }
 }
 
-impl Ord for RcString {
+impl<V: VecType, A: Allocator + Clone> Ord for GenericString<V, A> {
     fn cmp(&self, rhs: &Self) -> Ordering {
+        if self.ptr_eq(rhs) {
+            return Ordering::Equal;
+        }
         str::cmp(self, rhs)
     }
 }
 
-impl fmt::Debug for RcString {
+impl<V: VecType, A: Allocator + Clone> PartialEq<GenericString<V, A>> for str {
+    fn eq(&self, rhs: &GenericString<V, A>) -> bool {
+        rhs == self
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> PartialEq<GenericString<V, A>> for &str {
+    fn eq(&self, rhs: &GenericString<V, A>) -> bool {
+        rhs == *self
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> PartialEq<GenericString<V, A>> for String {
+    fn eq(&self, rhs: &GenericString<V, A>) -> bool {
+        rhs == self
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> PartialOrd<GenericString<V, A>> for str {
+    fn partial_cmp(&self, rhs: &GenericString<V, A>) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> PartialOrd<GenericString<V, A>> for &str {
+    fn partial_cmp(&self, rhs: &GenericString<V, A>) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> PartialOrd<GenericString<V, A>> for String {
+    fn partial_cmp(&self, rhs: &GenericString<V, A>) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> Hash for GenericString<V, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        str::hash(self, state)
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> Borrow<str> for GenericString<V, A> {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> AsRef<[u8]> for GenericString<V, A> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<V: VecType, A: Allocator + Clone> fmt::Debug for GenericString<V, A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         <str as fmt::Debug>::fmt(self, f)
     }
 }
 
-impl fmt::Display for RcString {
+impl<V: VecType, A: Allocator + Clone> fmt::Display for GenericString<V, A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         <str as fmt::Display>::fmt(self, f)
     }
 }
 
-impl fmt::Write for RcString {
+impl<V: VecType, A: Allocator + Clone> fmt::Write for GenericString<V, A> {
     fn write_str(&mut self, rhs: &str) -> fmt::Result {
         *self += rhs;
         Ok(())
     }
 }
 
-impl Extend<char> for RcString {
+impl<V: VecType, A: Allocator + Clone> Extend<char> for GenericString<V, A> {
     fn extend<I: IntoIterator<Item = char>>(&mut self, it: I) {
         for c in it {
             self.push(c);
@@ -133,13 +586,13 @@ impl Extend<char> for RcString {
     }
 }
 
-impl<'a> Extend<&'a char> for RcString {
+impl<'a, V: VecType, A: Allocator + Clone> Extend<&'a char> for GenericString<V, A> {
     fn extend<I: IntoIterator<Item = &'a char>>(&mut self, it: I) {
         self.extend(it.into_iter().copied())
     }
 }
 
-impl<'a> Extend<&'a str> for RcString {
+impl<'a, V: VecType, A: Allocator + Clone> Extend<&'a str> for GenericString<V, A> {
     fn extend<I: IntoIterator<Item = &'a str>>(&mut self, it: I) {
         for s in it {
             self.push_str(s);
@@ -147,23 +600,23 @@ impl<'a> Extend<&'a str> for RcString {
     }
 }
 
-impl Extend<RcString> for RcString {
-    fn extend<I: IntoIterator<Item = RcString>>(&mut self, it: I) {
+impl<V: VecType, A: Allocator + Clone> Extend<GenericString<V, A>> for GenericString<V, A> {
+    fn extend<I: IntoIterator<Item = GenericString<V, A>>>(&mut self, it: I) {
         for s in it {
             self.push_str(&s);
         }
     }
 }
 
-impl<'a> Extend<&'a RcString> for RcString {
-    fn extend<I: IntoIterator<Item = &'a RcString>>(&mut self, it: I) {
+impl<'a, V: VecType, A: Allocator + Clone> Extend<&'a GenericString<V, A>> for GenericString<V, A> {
+    fn extend<I: IntoIterator<Item = &'a GenericString<V, A>>>(&mut self, it: I) {
         for s in it {
             self.push_str(s);
         }
     }
 }
 
-impl Extend<String> for RcString {
+impl<V: VecType, A: Allocator + Clone> Extend<String> for GenericString<V, A> {
     fn extend<I: IntoIterator<Item = String>>(&mut self, it: I) {
         for s in it {
             self.push_str(&s);
@@ -171,7 +624,7 @@ impl Extend<String> for RcString {
     }
 }
 
-impl<'a> Extend<&'a String> for RcString {
+impl<'a, V: VecType, A: Allocator + Clone> Extend<&'a String> for GenericString<V, A> {
     fn extend<I: IntoIterator<Item = &'a String>>(&mut self, it: I) {
         for s in it {
             self.push_str(s);
@@ -179,7 +632,7 @@ impl<'a> Extend<&'a String> for RcString {
     }
 }
 
-impl iter::FromIterator<char> for RcString {
+impl<V: VecType> iter::FromIterator<char> for GenericString<V> {
     fn from_iter<I: IntoIterator<Item = char>>(it: I) -> Self {
         let mut this = Self::new();
         this.extend(it);
@@ -187,13 +640,13 @@ impl iter::FromIterator<char> for RcString {
     }
 }
 
-impl<'a> iter::FromIterator<&'a char> for RcString {
+impl<'a, V: VecType> iter::FromIterator<&'a char> for GenericString<V> {
     fn from_iter<I: IntoIterator<Item = &'a char>>(it: I) -> Self {
         it.into_iter().copied().collect()
     }
 }
 
-impl<'a> iter::FromIterator<&'a str> for RcString {
+impl<'a, V: VecType> iter::FromIterator<&'a str> for GenericString<V> {
     fn from_iter<I: IntoIterator<Item = &'a str>>(it: I) -> Self {
         let mut this = Self::new();
         this.extend(it);
@@ -201,8 +654,8 @@ impl<'a> iter::FromIterator<&'a str> for RcString {
     }
 }
 
-impl iter::FromIterator<RcString> for RcString {
-    fn from_iter<I: IntoIterator<Item = RcString>>(it: I) -> Self {
+impl<V: VecType> iter::FromIterator<GenericString<V>> for GenericString<V> {
+    fn from_iter<I: IntoIterator<Item = GenericString<V>>>(it: I) -> Self {
         let mut it = it.into_iter();
         match it.next() {
             Some(mut s) => {
@@ -214,8 +667,8 @@ impl iter::FromIterator<RcString> for RcString {
     }
 }
 
-impl<'a> iter::FromIterator<&'a RcString> for RcString {
-    fn from_iter<I: IntoIterator<Item = &'a RcString>>(it: I) -> Self {
+impl<'a, V: VecType> iter::FromIterator<&'a GenericString<V>> for GenericString<V> {
+    fn from_iter<I: IntoIterator<Item = &'a GenericString<V>>>(it: I) -> Self {
         let mut it = it.into_iter();
         match it.next() {
             Some(s) => {
@@ -228,7 +681,7 @@ impl<'a> iter::FromIterator<&'a RcString> for RcString {
     }
 }
 
-impl iter::FromIterator<String> for RcString {
+impl<V: VecType> iter::FromIterator<String> for GenericString<V> {
     fn from_iter<I: IntoIterator<Item = String>>(it: I) -> Self {
         let mut this = Self::new();
         this.extend(it);
@@ -236,7 +689,7 @@ impl iter::FromIterator<String> for RcString {
     }
 }
 
-impl<'a> iter::FromIterator<&'a String> for RcString {
+impl<'a, V: VecType> iter::FromIterator<&'a String> for GenericString<V> {
     fn from_iter<I: IntoIterator<Item = &'a String>>(it: I) -> Self {
         let mut this = Self::new();
         this.extend(it);
@@ -0,0 +1,121 @@
+use super::GenericString;
+use crate::base::VecType;
+use alloc::collections::btree_set::{self, BTreeSet};
+use core::fmt;
+use core::iter::FromIterator;
+
+/// Maps strings to a canonical shared handle, so equal interned strings share a buffer and can
+/// be compared with `ptr_eq` instead of comparing their contents.
+///
+/// The interner keeps every string alive until `gc` is called, which evicts the strings that
+/// nothing else refers to anymore.
+pub struct GenericInterner<V: VecType> {
+    set: BTreeSet<GenericString<V>>,
+}
+
+impl<V: VecType> GenericInterner<V> {
+    pub fn new() -> Self {
+        Self {
+            set: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the canonical handle for `s`, adding it to the interner if it isn't there yet.
+    pub fn intern(&mut self, s: &str) -> GenericString<V> {
+        if let Some(x) = self.set.get(s) {
+            return x.clone();
+        }
+        let x = GenericString::from(s);
+        self.set.insert(x.clone());
+        x
+    }
+
+    /// Returns the canonical handle for `s` if it has been interned.
+    pub fn get(&self, s: &str) -> Option<GenericString<V>> {
+        self.set.get(s).cloned()
+    }
+
+    pub fn contains(&self, s: &str) -> bool {
+        self.set.contains(s)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Evicts every string whose only handle is the one held by the interner, returning how
+    /// many were evicted.
+    pub fn gc(&mut self) -> usize {
+        let before = self.set.len();
+        // the interner's handle is never lent out, so a count of 1 can't go up concurrently
+        self.set.retain(|s| s.strong_count() > 1);
+        before - self.set.len()
+    }
+
+    /// Iterates over the canonical handles in sorted order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter(self.set.iter())
+    }
+}
+
+impl<V: VecType> Default for GenericInterner<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, V: VecType> Extend<&'a str> for GenericInterner<V> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, it: I) {
+        for s in it {
+            self.intern(s);
+        }
+    }
+}
+
+impl<'a, V: VecType> FromIterator<&'a str> for GenericInterner<V> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(it: I) -> Self {
+        let mut this = Self::new();
+        this.extend(it);
+        this
+    }
+}
+
+impl<'a, V: VecType> IntoIterator for &'a GenericInterner<V> {
+    type Item = &'a GenericString<V>;
+    type IntoIter = Iter<'a, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V: VecType> fmt::Debug for GenericInterner<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator returned by `GenericInterner::iter`.
+pub struct Iter<'a, V: VecType>(btree_set::Iter<'a, GenericString<V>>);
+
+impl<'a, V: VecType> Iterator for Iter<'a, V> {
+    type Item = &'a GenericString<V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, V: VecType> DoubleEndedIterator for Iter<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, V: VecType> ExactSizeIterator for Iter<'a, V> {}
@@ -0,0 +1,406 @@
+use super::substr::GenericStr;
+use super::GenericString;
+use crate::base::VecType;
+use crate::vec::slice::resolve_range;
+use crate::vec::tree::{self, Leaf, Leaves, SplitLeaf, Tree};
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter;
+use core::ops::{Add, AddAssign, RangeBounds};
+
+/// Leaves are only merged by copying while they stay this short, so edits never copy more than
+/// this many bytes per leaf. Longer strings are split into leaves of about this size.
+const MAX_LEAF: usize = 1024;
+
+/// What the indexing methods count, for a leaf or a whole subtree.
+#[derive(Copy, Clone, Default, Debug)]
+pub(crate) struct Summary {
+    bytes: usize,
+    chars: usize,
+    /// The number of `'\n'`s.
+    lines: usize,
+}
+
+impl Summary {
+    fn of(s: &str) -> Self {
+        Self {
+            bytes: s.len(),
+            chars: s.chars().count(),
+            lines: s.bytes().filter(|&b| b == b'\n').count(),
+        }
+    }
+}
+
+impl Add for Summary {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            lines: self.lines + other.lines,
+        }
+    }
+}
+
+impl AddAssign for Summary {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl tree::Summary for Summary {
+    fn len(self) -> usize {
+        self.bytes
+    }
+}
+
+impl<V: VecType> Leaf for GenericStr<V> {
+    type Summary = Summary;
+    fn summary(&self) -> Summary {
+        Summary::of(self)
+    }
+}
+
+impl<V: VecType> SplitLeaf for GenericStr<V> {
+    fn split_at(&self, at: usize) -> (Self, Self) {
+        (self.substr(..at), self.substr(at..))
+    }
+    fn merge(left: Self, right: Self) -> Result<Self, (Self, Self)> {
+        if left.len() + right.len() > MAX_LEAF {
+            return Err((left, right));
+        }
+        let mut s = GenericString::with_capacity(left.len() + right.len());
+        s.push_str(&left);
+        s.push_str(&right);
+        Ok(s.into())
+    }
+}
+
+/// A string stored as a tree of shared chunks, with every leaf at the same depth. Cloning is
+/// O(1), and `insert`, `remove`, `slice` and `concat` only rebuild the O(log n) nodes along the
+/// edges they touch, so snapshots of a large, frequently edited text stay cheap.
+///
+/// Byte offsets must fall on char boundaries, as with `str`. Lines are separated by `'\n'`, so
+/// a rope with `n` newlines has `n + 1` lines.
+pub struct GenericRope<V: VecType> {
+    tree: Tree<V, GenericStr<V>>,
+}
+
+impl<V: VecType> GenericRope<V> {
+    pub fn new() -> Self {
+        Self { tree: Tree::new() }
+    }
+
+    /// The length in bytes.
+    pub fn len(&self) -> usize {
+        self.tree.sum.bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.sum.bytes == 0
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.tree.sum.chars
+    }
+
+    pub fn len_lines(&self) -> usize {
+        self.tree.sum.lines + 1
+    }
+
+    /// Returns the char at char index `index`, or `None` if it is out of bounds.
+    pub fn char(&self, index: usize) -> Option<char> {
+        if index >= self.tree.sum.chars {
+            return None;
+        }
+        let (leaf, _, index) = self.tree.find_leaf(index, |s| s.chars);
+        leaf.chars().nth(index)
+    }
+
+    /// Converts a char index into a byte offset. Panics if `index > len_chars()`.
+    pub fn char_to_byte(&self, index: usize) -> usize {
+        assert!(
+            index <= self.tree.sum.chars,
+            "char index {} is out of bounds for {} chars",
+            index,
+            self.tree.sum.chars
+        );
+        if index == self.tree.sum.chars {
+            return self.tree.sum.bytes;
+        }
+        let (leaf, before, index) = self.tree.find_leaf(index, |s| s.chars);
+        before.bytes + leaf.char_indices().nth(index).unwrap().0
+    }
+
+    /// Converts a byte offset into a char index. Panics if `byte > len()` or it isn't on a char
+    /// boundary.
+    pub fn byte_to_char(&self, byte: usize) -> usize {
+        self.check_byte(byte);
+        if byte == self.tree.sum.bytes {
+            return self.tree.sum.chars;
+        }
+        let (leaf, before, byte) = self.tree.find_leaf(byte, |s| s.bytes);
+        before.chars + leaf[..byte].chars().count()
+    }
+
+    /// Returns the byte offset where line `line` starts. Panics if `line >= len_lines()`.
+    pub fn line_to_byte(&self, line: usize) -> usize {
+        assert!(
+            line <= self.tree.sum.lines,
+            "line {} is out of bounds for {} lines",
+            line,
+            self.len_lines()
+        );
+        if line == 0 {
+            return 0;
+        }
+        // the line starts just after the newline ending the line before it
+        let (leaf, before, newline) = self.tree.find_leaf(line - 1, |s| s.lines);
+        let (offset, _) = leaf.match_indices('\n').nth(newline).unwrap();
+        before.bytes + offset + 1
+    }
+
+    /// Returns the line containing byte offset `byte`. Panics if `byte > len()`.
+    pub fn byte_to_line(&self, byte: usize) -> usize {
+        self.check_byte(byte);
+        if byte == self.tree.sum.bytes {
+            return self.tree.sum.lines;
+        }
+        let (leaf, before, byte) = self.tree.find_leaf(byte, |s| s.bytes);
+        before.lines
+            + leaf.as_bytes()[..byte]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+    }
+
+    /// Returns line `line`, including its trailing newline if it has one.
+    /// Panics if `line >= len_lines()`.
+    pub fn line(&self, line: usize) -> Self {
+        let start = self.line_to_byte(line);
+        let end = if line < self.tree.sum.lines {
+            self.line_to_byte(line + 1)
+        } else {
+            self.tree.sum.bytes
+        };
+        self.slice(start..end)
+    }
+
+    fn check_byte(&self, byte: usize) {
+        assert!(
+            byte <= self.tree.sum.bytes,
+            "byte offset {} is out of bounds for length {}",
+            byte,
+            self.tree.sum.bytes
+        );
+    }
+
+    /// Returns the bytes in `range` as a new rope that shares chunks with this one.
+    /// Panics if the range is out of bounds or doesn't fall on char boundaries.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let (start, end) = resolve_range(range, self.tree.sum.bytes);
+        let (_, tail) = self.split_at(start);
+        let (mid, _) = tail.split_at(end - start);
+        mid
+    }
+
+    /// Returns the bytes before `at` and the bytes from `at` onwards.
+    /// Panics if `at > len()` or it isn't on a char boundary.
+    pub fn split_at(&self, at: usize) -> (Self, Self) {
+        self.check_byte(at);
+        let (left, right) = self.tree.split_at(at);
+        (Self { tree: left }, Self { tree: right })
+    }
+
+    /// Returns a rope with the contents of `self` followed by those of `other`.
+    pub fn concat(&self, other: &Self) -> Self {
+        Self {
+            tree: self.tree.concat(&other.tree),
+        }
+    }
+
+    /// Inserts `s` at byte offset `at`.
+    /// Panics if `at > len()` or it isn't on a char boundary.
+    pub fn insert(&mut self, at: usize, s: &str) {
+        let (left, right) = self.split_at(at);
+        *self = left.concat(&Self::from(s)).concat(&right);
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        *self = self.concat(&Self::from(s));
+    }
+
+    /// Removes the bytes in `range`.
+    /// Panics if the range is out of bounds or doesn't fall on char boundaries.
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = resolve_range(range, self.tree.sum.bytes);
+        let (left, tail) = self.split_at(start);
+        let (_, right) = tail.split_at(end - start);
+        *self = left.concat(&right);
+    }
+
+    /// Iterates over the chunks of the rope in order.
+    pub fn chunks(&self) -> Chunks<'_, V> {
+        Chunks {
+            leaves: self.tree.leaves(),
+        }
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.chunks().flat_map(str::bytes)
+    }
+
+    /// Copies the contents into a single string. A rope made of one whole string returns it
+    /// without copying.
+    pub fn to_flat(&self) -> GenericString<V> {
+        if let tree::Node::Leaf(s) = &self.tree.root {
+            return s.clone().into();
+        }
+        let mut flat = GenericString::with_capacity(self.tree.sum.bytes);
+        for chunk in self.chunks() {
+            flat.push_str(chunk);
+        }
+        flat
+    }
+}
+
+impl<V: VecType> From<GenericStr<V>> for GenericRope<V> {
+    /// Shares the buffer of `src`, splitting it into leaves without copying.
+    fn from(src: GenericStr<V>) -> Self {
+        let mut start = 0;
+        let leaves = iter::from_fn(|| {
+            if start == src.len() {
+                return None;
+            }
+            let mut end = src.len().min(start + MAX_LEAF);
+            while !src.is_char_boundary(end) {
+                end -= 1;
+            }
+            let leaf = src.substr(start..end);
+            start = end;
+            Some(leaf)
+        });
+        Self {
+            tree: Tree::from_leaves(leaves),
+        }
+    }
+}
+
+impl<V: VecType> From<GenericString<V>> for GenericRope<V> {
+    fn from(src: GenericString<V>) -> Self {
+        GenericStr::from(src).into()
+    }
+}
+
+impl<V: VecType> From<&str> for GenericRope<V> {
+    fn from(src: &str) -> Self {
+        GenericString::from(src).into()
+    }
+}
+
+impl<V: VecType> From<GenericRope<V>> for GenericString<V> {
+    fn from(src: GenericRope<V>) -> Self {
+        src.to_flat()
+    }
+}
+
+/// Iterates over the chunks of a `GenericRope`.
+pub struct Chunks<'a, V: VecType> {
+    leaves: Leaves<'a, V, GenericStr<V>>,
+}
+
+impl<'a, V: VecType> Iterator for Chunks<'a, V> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        // only an empty rope has an empty leaf
+        self.leaves
+            .next()
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+    }
+}
+
+impl<V: VecType> Clone for GenericRope<V> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+impl<V: VecType> Default for GenericRope<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType> fmt::Display for GenericRope<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl<V: VecType> fmt::Debug for GenericRope<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.to_flat(), f)
+    }
+}
+
+impl<V: VecType> PartialEq for GenericRope<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl<V: VecType> PartialEq<str> for GenericRope<V> {
+    fn eq(&self, other: &str) -> bool {
+        self.len() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl<V: VecType> PartialEq<&str> for GenericRope<V> {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl<V: VecType> Eq for GenericRope<V> {}
+
+impl<V: VecType> PartialOrd for GenericRope<V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V: VecType> Ord for GenericRope<V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bytes().cmp(other.bytes())
+    }
+}
+
+impl<V: VecType> Hash for GenericRope<V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // feed the hasher fixed-size blocks, so equal ropes hash the same however they are chunked
+        let mut block = [0; 64];
+        let mut n = 0;
+        for b in self.bytes() {
+            block[n] = b;
+            n += 1;
+            if n == block.len() {
+                state.write(&block);
+                n = 0;
+            }
+        }
+        state.write(&block[..n]);
+        state.write_u8(0xff);
+    }
+}
@@ -0,0 +1,260 @@
+use super::GenericString;
+use crate::base::VecType;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+
+/// How many bytes fit in the handle itself. Together with the length and the discriminant, this
+/// makes a small string three words long.
+pub const INLINE_CAP: usize = 3 * mem::size_of::<usize>() - 2;
+
+enum Repr<V: VecType> {
+    Inline { len: u8, buf: [u8; INLINE_CAP] },
+    Heap(GenericString<V>),
+}
+
+/// A string that stores up to `INLINE_CAP` bytes in the handle itself, and moves them into a
+/// shared `GenericString` once it grows past that. Inline strings are copied when cloned, so
+/// call `make_shared` first if a short string will be cloned many times.
+pub struct GenericSmallString<V: VecType> {
+    repr: Repr<V>,
+}
+
+impl<V: VecType> GenericSmallString<V> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline {
+                len: 0,
+                buf: [0; INLINE_CAP],
+            },
+        }
+    }
+
+    /// Whether the contents are stored in the handle rather than a shared buffer.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+
+    /// Moves the contents into a shared buffer, if they aren't in one already, so clones of this
+    /// handle share them.
+    pub fn make_shared(&mut self) {
+        if self.is_inline() {
+            self.repr = Repr::Heap(GenericString::from(self.as_str()));
+        }
+    }
+
+    /// Converts this into a shared string, copying the contents into one if they are inline.
+    pub fn into_shared(mut self) -> GenericString<V> {
+        self.make_shared();
+        match self.repr {
+            Repr::Heap(heap) => heap,
+            Repr::Inline { .. } => unreachable!("string was just made shared"),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.repr {
+            // SAFETY: the first `len` bytes are always valid UTF-8
+            Repr::Inline { len, buf } => unsafe {
+                core::str::from_utf8_unchecked(&buf[..*len as usize])
+            },
+            Repr::Heap(heap) => heap,
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        match &mut self.repr {
+            Repr::Inline { len, buf } => {
+                let start = *len as usize;
+                if let Some(dest) = buf.get_mut(start..start + s.len()) {
+                    dest.copy_from_slice(s.as_bytes());
+                    *len += s.len() as u8;
+                    return;
+                }
+            }
+            Repr::Heap(heap) => return heap.push_str(s),
+        }
+        // out of room, so move to the heap with room to grow
+        let mut heap = GenericString::with_capacity((self.len() + s.len()) * 2);
+        heap.push_str(self.as_str());
+        heap.push_str(s);
+        self.repr = Repr::Heap(heap);
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        self.truncate(self.len() - c.len_utf8());
+        Some(c)
+    }
+
+    /// Shortens the string to `new_len` bytes. Does nothing if it is already shorter.
+    /// Panics if `new_len` isn't on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len >= self.len() {
+            return;
+        }
+        assert!(
+            self.is_char_boundary(new_len),
+            "new_len is not on a char boundary"
+        );
+        match &mut self.repr {
+            Repr::Inline { len, .. } => *len = new_len as u8,
+            Repr::Heap(heap) => heap.truncate(new_len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<V: VecType> Deref for GenericSmallString<V> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<V: VecType> DerefMut for GenericSmallString<V> {
+    fn deref_mut(&mut self) -> &mut str {
+        match &mut self.repr {
+            // SAFETY: the first `len` bytes are always valid UTF-8
+            Repr::Inline { len, buf } => unsafe {
+                core::str::from_utf8_unchecked_mut(&mut buf[..*len as usize])
+            },
+            Repr::Heap(heap) => heap,
+        }
+    }
+}
+
+impl<V: VecType> Clone for GenericSmallString<V> {
+    fn clone(&self) -> Self {
+        let repr = match &self.repr {
+            Repr::Inline { len, buf } => Repr::Inline {
+                len: *len,
+                buf: *buf,
+            },
+            Repr::Heap(heap) => Repr::Heap(heap.clone()),
+        };
+        Self { repr }
+    }
+}
+
+impl<V: VecType> Default for GenericSmallString<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType> From<&str> for GenericSmallString<V> {
+    fn from(src: &str) -> Self {
+        let mut this = Self::new();
+        this.push_str(src);
+        this
+    }
+}
+
+impl<V: VecType> From<GenericString<V>> for GenericSmallString<V> {
+    fn from(src: GenericString<V>) -> Self {
+        Self {
+            repr: Repr::Heap(src),
+        }
+    }
+}
+
+impl<V: VecType> AsRef<str> for GenericSmallString<V> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<V: VecType> Borrow<str> for GenericSmallString<V> {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<S: AsRef<str> + ?Sized, V: VecType> PartialEq<S> for GenericSmallString<V> {
+    fn eq(&self, rhs: &S) -> bool {
+        str::eq(self, rhs.as_ref())
+    }
+}
+
+impl<V: VecType> Eq for GenericSmallString<V> {}
+
+impl<S: AsRef<str> + ?Sized, V: VecType> PartialOrd<S> for GenericSmallString<V> {
+    fn partial_cmp(&self, rhs: &S) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_ref())
+    }
+}
+
+impl<V: VecType> Ord for GenericSmallString<V> {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        str::cmp(self, rhs)
+    }
+}
+
+impl<V: VecType> Hash for GenericSmallString<V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        str::hash(self, state)
+    }
+}
+
+impl<V: VecType> fmt::Debug for GenericSmallString<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <str as fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<V: VecType> fmt::Display for GenericSmallString<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <str as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl<V: VecType> fmt::Write for GenericSmallString<V> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<V: VecType> Extend<char> for GenericSmallString<V> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, it: I) {
+        for c in it {
+            self.push(c);
+        }
+    }
+}
+
+impl<'a, V: VecType> Extend<&'a str> for GenericSmallString<V> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, it: I) {
+        for s in it {
+            self.push_str(s);
+        }
+    }
+}
+
+impl<V: VecType> FromIterator<char> for GenericSmallString<V> {
+    fn from_iter<I: IntoIterator<Item = char>>(it: I) -> Self {
+        let mut this = Self::new();
+        this.extend(it);
+        this
+    }
+}
+
+impl<'a, V: VecType> FromIterator<&'a str> for GenericSmallString<V> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(it: I) -> Self {
+        let mut this = Self::new();
+        this.extend(it);
+        this
+    }
+}
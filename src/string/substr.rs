@@ -0,0 +1,278 @@
+use super::GenericString;
+use crate::base::VecType;
+use crate::vec::generic_vec::GenericVec;
+use crate::vec::slice::{resolve_range, GenericSlice};
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, DerefMut, RangeBounds};
+use core::str::{Lines, Split, SplitWhitespace};
+
+/// A substring that shares the buffer of the string it was taken from.
+pub struct GenericStr<V: VecType> {
+    base: GenericSlice<V, u8>,
+}
+
+/// Returns the byte range of `part` within `whole`, panicking if it isn't a subslice of it.
+fn offset_of(whole: &str, part: &str) -> (usize, usize) {
+    let start = (part.as_ptr() as usize).wrapping_sub(whole.as_ptr() as usize);
+    assert!(
+        start <= whole.len() && part.len() <= whole.len() - start,
+        "substring is not part of this string"
+    );
+    (start, start + part.len())
+}
+
+impl<V: VecType> GenericString<V> {
+    /// Returns a shared handle to the bytes in `range`.
+    /// Panics if the range is out of bounds or doesn't fall on char boundaries.
+    pub fn substr<R: RangeBounds<usize>>(&self, range: R) -> GenericStr<V> {
+        GenericStr::from(self.clone()).substr(range)
+    }
+
+    /// Returns a shared handle to `part`, which must be borrowed from this string.
+    pub fn substr_of(&self, part: &str) -> GenericStr<V> {
+        let (start, end) = offset_of(self, part);
+        self.substr(start..end)
+    }
+
+    /// Like `str::split`, but yields shared handles.
+    pub fn split_shared<'a>(&'a self, sep: &'a str) -> SharedSubstrs<V, Split<'a, &'a str>> {
+        SharedSubstrs::new(self.clone().into(), self.split(sep))
+    }
+
+    /// Like `str::lines`, but yields shared handles.
+    pub fn lines_shared(&self) -> SharedSubstrs<V, Lines<'_>> {
+        SharedSubstrs::new(self.clone().into(), self.lines())
+    }
+
+    /// Like `str::split_whitespace`, but yields shared handles.
+    pub fn split_whitespace_shared(&self) -> SharedSubstrs<V, SplitWhitespace<'_>> {
+        SharedSubstrs::new(self.clone().into(), self.split_whitespace())
+    }
+
+    /// Like `str::trim`, but returns a shared handle.
+    pub fn trim_shared(&self) -> GenericStr<V> {
+        self.substr_of(self.trim())
+    }
+}
+
+impl<V: VecType> GenericStr<V> {
+    /// Returns a shared handle to the bytes in `range`, relative to this substring.
+    /// Panics if the range is out of bounds or doesn't fall on char boundaries.
+    pub fn substr<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let (start, end) = resolve_range(range, self.len());
+        assert!(
+            self.is_char_boundary(start) && self.is_char_boundary(end),
+            "byte range {}..{} does not fall on char boundaries",
+            start,
+            end
+        );
+        Self {
+            base: self.base.slice(start..end),
+        }
+    }
+
+    /// Returns a shared handle to `part`, which must be borrowed from this substring.
+    pub fn substr_of(&self, part: &str) -> Self {
+        let (start, end) = offset_of(self, part);
+        self.substr(start..end)
+    }
+
+    /// Like `str::split`, but yields shared handles.
+    pub fn split_shared<'a>(&'a self, sep: &'a str) -> SharedSubstrs<V, Split<'a, &'a str>> {
+        SharedSubstrs::new(self.clone(), self.split(sep))
+    }
+
+    /// Like `str::lines`, but yields shared handles.
+    pub fn lines_shared(&self) -> SharedSubstrs<V, Lines<'_>> {
+        SharedSubstrs::new(self.clone(), self.lines())
+    }
+
+    /// Like `str::split_whitespace`, but yields shared handles.
+    pub fn split_whitespace_shared(&self) -> SharedSubstrs<V, SplitWhitespace<'_>> {
+        SharedSubstrs::new(self.clone(), self.split_whitespace())
+    }
+
+    /// Like `str::trim`, but returns a shared handle.
+    pub fn trim_shared(&self) -> Self {
+        self.substr_of(self.trim())
+    }
+
+    /// Whether both handles cover the same bytes of the same string.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self
+    }
+}
+
+impl<V: VecType> From<GenericString<V>> for GenericStr<V> {
+    fn from(src: GenericString<V>) -> Self {
+        Self {
+            base: src.base.into(),
+        }
+    }
+}
+
+impl<V: VecType> From<GenericStr<V>> for GenericString<V> {
+    /// Reuses the buffer if the substring covers all of it; otherwise copies the substring.
+    fn from(src: GenericStr<V>) -> Self {
+        Self {
+            base: GenericVec::from(src.base),
+        }
+    }
+}
+
+impl<V: VecType> Deref for GenericStr<V> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        // SAFETY: the range was checked to fall on char boundaries of a valid string
+        unsafe { core::str::from_utf8_unchecked(&self.base) }
+    }
+}
+
+impl<V: VecType> DerefMut for GenericStr<V> {
+    fn deref_mut(&mut self) -> &mut str {
+        unsafe { core::str::from_utf8_unchecked_mut(&mut self.base) }
+    }
+}
+
+impl<V: VecType> AsRef<str> for GenericStr<V> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<V: VecType> Clone for GenericStr<V> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+        }
+    }
+}
+
+impl<V: VecType> Default for GenericStr<V> {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+        }
+    }
+}
+
+impl<S: AsRef<str> + ?Sized, V: VecType> PartialEq<S> for GenericStr<V> {
+    fn eq(&self, rhs: &S) -> bool {
+        str::eq(self, rhs.as_ref())
+    }
+}
+
+impl<V: VecType> Eq for GenericStr<V> {}
+
+impl<S: AsRef<str> + ?Sized, V: VecType> PartialOrd<S> for GenericStr<V> {
+    fn partial_cmp(&self, rhs: &S) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_ref())
+    }
+}
+
+impl<V: VecType> Ord for GenericStr<V> {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        str::cmp(self, rhs)
+    }
+}
+
+impl<V: VecType> PartialEq<GenericStr<V>> for str {
+    fn eq(&self, rhs: &GenericStr<V>) -> bool {
+        str::eq(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType> PartialEq<GenericStr<V>> for &str {
+    fn eq(&self, rhs: &GenericStr<V>) -> bool {
+        str::eq(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType> PartialEq<GenericStr<V>> for String {
+    fn eq(&self, rhs: &GenericStr<V>) -> bool {
+        str::eq(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType> PartialOrd<GenericStr<V>> for str {
+    fn partial_cmp(&self, rhs: &GenericStr<V>) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType> PartialOrd<GenericStr<V>> for &str {
+    fn partial_cmp(&self, rhs: &GenericStr<V>) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType> PartialOrd<GenericStr<V>> for String {
+    fn partial_cmp(&self, rhs: &GenericStr<V>) -> Option<Ordering> {
+        str::partial_cmp(self, rhs.as_str())
+    }
+}
+
+impl<V: VecType> Hash for GenericStr<V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        str::hash(self, state)
+    }
+}
+
+impl<V: VecType> Borrow<str> for GenericStr<V> {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<V: VecType> fmt::Debug for GenericStr<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <str as fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<V: VecType> fmt::Display for GenericStr<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <str as fmt::Display>::fmt(self, f)
+    }
+}
+
+/// Iterator that turns the substrings yielded by a `str` iterator into shared handles.
+pub struct SharedSubstrs<V: VecType, I> {
+    src: GenericStr<V>,
+    inner: I,
+}
+
+impl<V: VecType, I> SharedSubstrs<V, I> {
+    fn new(src: GenericStr<V>, inner: I) -> Self {
+        Self { src, inner }
+    }
+}
+
+impl<'a, V: VecType, I: Iterator<Item = &'a str>> Iterator for SharedSubstrs<V, I> {
+    type Item = GenericStr<V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let part = self.inner.next()?;
+        Some(self.src.substr_of(part))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, V: VecType, I: DoubleEndedIterator<Item = &'a str>> DoubleEndedIterator
+    for SharedSubstrs<V, I>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let part = self.inner.next_back()?;
+        Some(self.src.substr_of(part))
+    }
+}
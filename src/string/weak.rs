@@ -0,0 +1,113 @@
+use super::{GenericString, StrongArcString, StrongRcString};
+use crate::vec::strong_weak::{ArcWeakVec, WeakVec};
+use core::fmt;
+
+impl StrongRcString {
+    pub fn downgrade(&self) -> RcWeakString {
+        RcWeakString {
+            base: self.base.downgrade(),
+        }
+    }
+}
+
+/// A weak handle to a `StrongRcString` that doesn't keep its contents alive.
+pub struct RcWeakString {
+    base: WeakVec<u8>,
+}
+
+impl RcWeakString {
+    pub fn upgrade(&self) -> Option<StrongRcString> {
+        Some(GenericString {
+            base: self.base.upgrade()?,
+        })
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+
+    pub fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+}
+
+impl Clone for RcWeakString {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for RcWeakString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RcWeakString(")?;
+
+        match self.upgrade() {
+            Some(s) => fmt::Debug::fmt(&s, f)?,
+            None => f.write_str("<dropped>")?,
+        }
+
+        f.write_str(")")?;
+        Ok(())
+    }
+}
+
+impl StrongArcString {
+    pub fn downgrade(&self) -> ArcWeakString {
+        ArcWeakString {
+            base: self.base.downgrade(),
+        }
+    }
+}
+
+/// A weak handle to a `StrongArcString` that doesn't keep its contents alive.
+pub struct ArcWeakString {
+    base: ArcWeakVec<u8>,
+}
+
+impl ArcWeakString {
+    pub fn upgrade(&self) -> Option<StrongArcString> {
+        Some(GenericString {
+            base: self.base.upgrade()?,
+        })
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+
+    pub fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+}
+
+impl Clone for ArcWeakString {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ArcWeakString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ArcWeakString(")?;
+
+        match self.upgrade() {
+            Some(s) => fmt::Debug::fmt(&s, f)?,
+            None => f.write_str("<dropped>")?,
+        }
+
+        f.write_str(")")?;
+        Ok(())
+    }
+}
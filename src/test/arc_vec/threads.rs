@@ -0,0 +1,121 @@
+use crate::string::ArcString;
+use crate::vec::ArcVec;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::mem;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+pub fn read_from_many_threads() {
+    let v = ArcVec::from_iter(0..100);
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let v = v.clone();
+            thread::spawn(move || v.iter().sum::<i32>())
+        })
+        .collect();
+
+    for h in handles {
+        assert_eq!(h.join().unwrap(), 4950);
+    }
+}
+
+#[test]
+pub fn write_after_share_copies() {
+    let v1 = ArcVec::from_iter(0..4);
+    let v2 = v1.clone();
+    let v2 = thread::spawn(move || {
+        let mut v2 = v2;
+        v2.push(4);
+        v2
+    })
+    .join()
+    .unwrap();
+    assert_eq!(&*v1, [0, 1, 2, 3]);
+    assert_eq!(&*v2, [0, 1, 2, 3, 4]);
+}
+
+#[test]
+pub fn contents_dropped_once() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    struct A;
+    impl Drop for A {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let v = ArcVec::from_iter((0..10).map(|_| A));
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let v = v.clone();
+            thread::spawn(move || mem::drop(v))
+        })
+        .collect();
+    mem::drop(v);
+
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+pub fn upgrade_across_threads() {
+    let v = ArcVec::from_iter(0..4);
+    let w = v.downgrade();
+    let w = thread::spawn(move || {
+        assert_eq!(&*w.upgrade().unwrap(), [0, 1, 2, 3]);
+        w
+    })
+    .join()
+    .unwrap();
+    mem::drop(v);
+    assert_eq!(w.upgrade(), None);
+}
+
+#[test]
+pub fn try_unwrap_with_weak() {
+    let v = ArcVec::from_iter(0..4);
+    let w = v.downgrade();
+    let v2 = v.clone();
+    let v = v.try_unwrap().unwrap_err();
+    mem::drop(v2);
+    assert_eq!(v.try_unwrap().unwrap(), [0, 1, 2, 3]);
+    assert_eq!(w.upgrade(), None);
+}
+
+#[test]
+pub fn try_unwrap_races_with_upgrade() {
+    for _ in 0..100 {
+        let v = ArcVec::from_iter(0..4);
+        let w = v.downgrade();
+        let upgraded = thread::spawn(move || w.upgrade());
+        match v.try_unwrap() {
+            Ok(inner) => assert_eq!(inner, [0, 1, 2, 3]),
+            Err(v) => assert_eq!(&*v, [0, 1, 2, 3]),
+        }
+        if let Some(v) = upgraded.join().unwrap() {
+            assert_eq!(&*v, [0, 1, 2, 3]);
+        }
+    }
+}
+
+#[test]
+pub fn weak_prevents_in_place_mutation() {
+    let mut v = ArcVec::from_iter(0..4);
+    let w = v.downgrade();
+    v.push(4);
+    assert_eq!(&*v, [0, 1, 2, 3, 4]);
+    assert_eq!(w.upgrade(), None);
+}
+
+#[test]
+pub fn string_across_threads() {
+    let s = ArcString::from("hello");
+    let s2 = s.clone();
+    let s2 = thread::spawn(move || s2 + ", world").join().unwrap();
+    assert_eq!(s, "hello");
+    assert_eq!(s2, "hello, world");
+}
@@ -1,2 +1,3 @@
+pub mod arc_vec;
 pub mod rc_vec;
 pub mod string;
@@ -0,0 +1,77 @@
+use crate::vec::{RcVec, UniqueRcVec};
+use alloc::vec::Vec;
+use allocator_api2::alloc::{AllocError, Allocator, Global};
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::mem;
+use core::ptr::NonNull;
+
+/// Forwards to `Global`, keeping track of how many blocks are live.
+#[derive(Default)]
+pub struct Tracker {
+    pub allocs: Cell<usize>,
+    pub live: Cell<usize>,
+}
+
+unsafe impl Allocator for Tracker {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        self.live.set(self.live.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+pub fn handle_size() {
+    assert_eq!(
+        mem::size_of::<RcVec<i32, &Tracker>>(),
+        mem::size_of::<usize>()
+    );
+}
+
+#[test]
+pub fn allocates_in_allocator() {
+    let tracker = Tracker::default();
+    let mut v = RcVec::with_capacity_in(2, &tracker);
+    assert_eq!(tracker.live.get(), 1);
+    v.extend(0..100);
+    assert!(tracker.allocs.get() > 1);
+    assert_eq!(tracker.live.get(), 1);
+    assert!(core::ptr::eq(*v.allocator(), &tracker));
+
+    // copies made on write share the allocator
+    let v2 = v.clone();
+    v.push(100);
+    assert_eq!(tracker.live.get(), 2);
+    assert_eq!(v.len(), 101);
+    assert_eq!(v2.len(), 100);
+    drop(v);
+    drop(v2);
+    assert_eq!(tracker.live.get(), 0);
+}
+
+#[test]
+pub fn frees_on_every_exit() {
+    let tracker = Tracker::default();
+    let v = RcVec::from_iter_in(0..10, &tracker);
+    assert_eq!(v.iter().sum::<i32>(), 45);
+    assert_eq!(v.clone().into_iter().collect::<Vec<_>>().len(), 10);
+    assert_eq!(v.try_unwrap().map(|v| v.len()), Ok(10));
+
+    let mut v = RcVec::from_iter_in(0..10, &tracker);
+    let tail = v.split_off(4);
+    assert!(core::ptr::eq(*tail.allocator(), &tracker));
+    assert_eq!(v.into_iter().count(), 4);
+    drop(tail);
+
+    let mut u = UniqueRcVec::new_in(&tracker);
+    u.push(Vec::from([1]));
+    let v = u.freeze();
+    assert!(v.into_inner().is_some());
+    assert_eq!(tracker.live.get(), 0);
+}
@@ -0,0 +1,122 @@
+use crate::rc_vec;
+use crate::vec::RcVec;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::mem;
+
+#[test]
+pub fn make_mut_unique_in_place() {
+    let mut v = rc_vec![1, 2, 3];
+    let ptr = v.as_ptr();
+    v.make_mut()[0] = 10;
+    assert_eq!(&*v, [10, 2, 3]);
+    assert_eq!(v.as_ptr(), ptr);
+    v.make_mut().push(4);
+    v.make_mut().truncate(2);
+    assert_eq!(&*v, [10, 2]);
+}
+
+#[test]
+pub fn make_mut_shared_copies() {
+    let mut v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    v1.make_mut().push(4);
+    assert_eq!(&*v1, [1, 2, 3, 4]);
+    assert_eq!(&*v2, [1, 2, 3]);
+}
+
+#[test]
+pub fn try_unwrap() {
+    let v1 = rc_vec![String::from("a"), String::from("b")];
+    let v2 = v1.clone();
+    let v1 = v1.try_unwrap().unwrap_err();
+    mem::drop(v2);
+    assert_eq!(v1.try_unwrap().unwrap(), ["a", "b"]);
+}
+
+#[test]
+pub fn try_unwrap_with_weak() {
+    let v = rc_vec![1, 2, 3];
+    let w = v.downgrade();
+    assert_eq!(v.try_unwrap().unwrap(), [1, 2, 3]);
+    assert_eq!(w.upgrade(), None);
+}
+
+#[test]
+pub fn into_inner_exactly_once() {
+    let v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    assert_eq!(v1.into_inner(), None);
+    assert_eq!(v2.into_inner(), Some(Vec::from([1, 2, 3])));
+}
+
+#[test]
+pub fn into_inner_does_not_drop_moved_values() {
+    let drops = RefCell::new(0);
+    struct A<'a>(&'a RefCell<i32>);
+    impl Drop for A<'_> {
+        fn drop(&mut self) {
+            *self.0.borrow_mut() += 1;
+        }
+    }
+
+    let v: RcVec<_> = (0..3).map(|_| A(&drops)).collect();
+    let inner = v.into_inner().unwrap();
+    assert_eq!(*drops.borrow(), 0);
+    mem::drop(inner);
+    assert_eq!(*drops.borrow(), 3);
+}
+
+#[test]
+pub fn unwrap_or_clone() {
+    let v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    assert_eq!(v1.unwrap_or_clone(), [1, 2, 3]);
+    assert_eq!(v2.unwrap_or_clone(), [1, 2, 3]);
+}
+
+#[test]
+pub fn from_std() {
+    let v: RcVec<_> = Vec::from([1, 2, 3]).into();
+    assert_eq!(&*v, [1, 2, 3]);
+    let v: RcVec<_> = Box::<[i32]>::from([1, 2, 3]).into();
+    assert_eq!(&*v, [1, 2, 3]);
+    let v: RcVec<_> = Rc::<[i32]>::from([1, 2, 3]).into();
+    assert_eq!(&*v, [1, 2, 3]);
+    let v: RcVec<_> = [1, 2, 3][..].into();
+    assert_eq!(&*v, [1, 2, 3]);
+}
+
+#[test]
+pub fn from_std_moves_elements() {
+    let drops = RefCell::new(0);
+    struct A<'a>(&'a RefCell<i32>);
+    impl Drop for A<'_> {
+        fn drop(&mut self) {
+            *self.0.borrow_mut() += 1;
+        }
+    }
+
+    let vec: Vec<_> = (0..3).map(|_| A(&drops)).collect();
+    let v = RcVec::from(vec);
+    assert_eq!(*drops.borrow(), 0);
+    let boxed: Box<[_]> = (0..2).map(|_| A(&drops)).collect();
+    let v2 = RcVec::from(boxed);
+    assert_eq!(*drops.borrow(), 0);
+    mem::drop((v, v2));
+    assert_eq!(*drops.borrow(), 5);
+}
+
+#[test]
+pub fn into_std() {
+    let v = rc_vec![1, 2, 3];
+    let vec: Vec<_> = v.clone().into();
+    let boxed: Box<[_]> = v.clone().into();
+    let rc: Rc<[_]> = v.into();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(*boxed, [1, 2, 3]);
+    assert_eq!(*rc, [1, 2, 3]);
+}
@@ -0,0 +1,77 @@
+use crate::rc_vec;
+use crate::vec::{AcycVec, ArcVec};
+use core::cmp::Ordering;
+use core::iter::FromIterator;
+use core::mem;
+
+#[test]
+pub fn strong_and_weak_counts() {
+    let v1 = rc_vec![1, 2, 3];
+    assert_eq!((v1.strong_count(), v1.weak_count()), (1, 0));
+    let v2 = v1.clone();
+    let w = v1.downgrade();
+    assert_eq!((v1.strong_count(), v1.weak_count()), (2, 1));
+    assert_eq!((w.strong_count(), w.weak_count()), (2, 1));
+    mem::drop(v1);
+    mem::drop(v2);
+    assert_eq!((w.strong_count(), w.weak_count()), (0, 1));
+}
+
+#[test]
+pub fn atomic_counts() {
+    let v1 = ArcVec::from_iter(0..3);
+    let v2 = v1.clone();
+    let w = v1.downgrade();
+    assert_eq!((v1.strong_count(), v1.weak_count()), (2, 1));
+    mem::drop(v1);
+    mem::drop(v2);
+    assert_eq!((w.strong_count(), w.weak_count()), (0, 1));
+}
+
+#[test]
+pub fn acyclic_counts() {
+    let v1 = AcycVec::from_iter(0..3);
+    let v2 = v1.clone();
+    assert_eq!((v2.strong_count(), v2.weak_count()), (2, 0));
+}
+
+#[test]
+pub fn is_unique() {
+    let v1 = rc_vec![1, 2, 3];
+    assert!(v1.is_unique());
+    let w = v1.downgrade();
+    assert!(!v1.is_unique());
+    mem::drop(w);
+    let v2 = v1.clone();
+    assert!(!v1.is_unique());
+    mem::drop(v2);
+    assert!(v1.is_unique());
+}
+
+#[test]
+pub fn ptr_eq() {
+    let v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    let v3 = rc_vec![1, 2, 3];
+    assert!(v1.ptr_eq(&v2));
+    assert!(!v1.ptr_eq(&v3));
+    assert_eq!(v1.as_ptr(), v2.as_ptr());
+    assert!(v1.downgrade().ptr_eq(&v2.downgrade()));
+    assert_eq!(v1.downgrade().as_ptr(), v1.as_ptr());
+}
+
+#[test]
+pub fn cmp_same_buffer() {
+    let v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    assert_eq!(v1.cmp(&v2), Ordering::Equal);
+}
+
+#[test]
+pub fn eq_same_buffer_not_reflexive() {
+    let v1 = rc_vec![f64::NAN];
+    let v2 = v1.clone();
+    assert!(v1.ptr_eq(&v2));
+    assert_ne!(v1, v2);
+    assert_eq!(v1.partial_cmp(&v2), None);
+}
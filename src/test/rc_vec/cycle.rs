@@ -0,0 +1,115 @@
+use crate::vec::cycle::{collect_cycles, CcVec, Trace, Tracer};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::mem;
+
+struct Node<'a> {
+    id: i32,
+    links: RefCell<Vec<CcVec<Node<'a>>>>,
+    dropped: &'a RefCell<Vec<i32>>,
+}
+
+impl<'a> Drop for Node<'a> {
+    fn drop(&mut self) {
+        self.dropped.borrow_mut().push(self.id);
+    }
+}
+
+unsafe impl<'a> Trace for Node<'a> {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        self.links.trace(tracer);
+    }
+}
+
+fn node<'a>(id: i32, dropped: &'a RefCell<Vec<i32>>) -> CcVec<Node<'a>> {
+    CcVec::from(vec![Node {
+        id,
+        links: RefCell::new(Vec::new()),
+        dropped,
+    }])
+}
+
+fn link<'a>(from: &CcVec<Node<'a>>, to: &CcVec<Node<'a>>) {
+    from[0].links.borrow_mut().push(to.clone());
+}
+
+fn sorted(q: &RefCell<Vec<i32>>) -> Vec<i32> {
+    let mut v = q.borrow().clone();
+    v.sort();
+    v
+}
+
+#[test]
+pub fn cycle_collected() {
+    let q = RefCell::new(Vec::new());
+    let a = node(1, &q);
+    let b = node(2, &q);
+    link(&a, &b);
+    link(&b, &a);
+    mem::drop(a);
+    mem::drop(b);
+    assert_eq!(sorted(&q), [0; 0]);
+    collect_cycles();
+    assert_eq!(sorted(&q), [1, 2]);
+}
+
+#[test]
+pub fn self_cycle_collected() {
+    let q = RefCell::new(Vec::new());
+    let a = node(1, &q);
+    link(&a, &a);
+    mem::drop(a);
+    collect_cycles();
+    assert_eq!(sorted(&q), [1]);
+}
+
+#[test]
+pub fn reachable_cycle_kept() {
+    let q = RefCell::new(Vec::new());
+    let a = node(1, &q);
+    let b = node(2, &q);
+    let c = node(3, &q);
+    link(&a, &b);
+    link(&b, &a);
+    link(&b, &c);
+    mem::drop(b);
+    mem::drop(c);
+    collect_cycles();
+    assert_eq!(sorted(&q), [0; 0]);
+    assert_eq!(a[0].links.borrow()[0][0].id, 2);
+    assert_eq!(a.strong_count(), 2);
+    mem::drop(a);
+    collect_cycles();
+    assert_eq!(sorted(&q), [1, 2, 3]);
+}
+
+#[test]
+pub fn acyclic_freed_without_collecting() {
+    let q = RefCell::new(Vec::new());
+    let a = node(1, &q);
+    let b = node(2, &q);
+    link(&a, &b);
+    // leaves `b` in the list of possible roots
+    mem::drop(b);
+    mem::drop(a);
+    assert_eq!(sorted(&q), [1, 2]);
+    collect_cycles();
+    assert_eq!(sorted(&q), [1, 2]);
+}
+
+#[test]
+pub fn possible_root_not_moved_by_mutation() {
+    let mut a: CcVec<i32> = CcVec::from(vec![0]);
+    let b = a.clone();
+    drop(b);
+    for i in 0..1000 {
+        a.push(i)
+    }
+    collect_cycles();
+    assert_eq!(a.len(), 1001);
+    assert_eq!(a[1000], 999);
+    // once collected, the vector is no longer remembered and mutates in place again
+    let ptr = a.as_ptr();
+    a[0] = 5;
+    assert_eq!(a.as_ptr(), ptr);
+}
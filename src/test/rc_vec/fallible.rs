@@ -0,0 +1,107 @@
+use crate::error::{TryMutError, TryReserveError};
+use crate::rc_vec;
+use crate::vec::RcVec;
+use alloc::vec::Vec;
+
+#[test]
+pub fn try_push_unique() {
+    let mut v = rc_vec![1, 2, 3];
+    v.try_push(4).unwrap();
+    assert_eq!(&*v, [1, 2, 3, 4]);
+}
+
+#[test]
+pub fn try_push_shared() {
+    let mut v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    v1.try_push(4).unwrap();
+    assert_eq!(&*v1, [1, 2, 3, 4]);
+    assert_eq!(&*v2, [1, 2, 3]);
+}
+
+#[test]
+pub fn try_pop() {
+    let mut v = rc_vec![1, 2];
+    assert_eq!(v.try_pop(), Ok(Some(2)));
+    assert_eq!(v.try_pop(), Ok(Some(1)));
+    assert_eq!(v.try_pop(), Ok(None));
+}
+
+#[test]
+pub fn try_insert_remove() {
+    let mut v1 = rc_vec![1, 2, 4];
+    let v2 = v1.clone();
+    v1.try_insert(2, 3).unwrap();
+    v1.try_insert(0, 0).unwrap();
+    assert_eq!(&*v1, [0, 1, 2, 3, 4]);
+    assert_eq!(v1.try_remove(1), Ok(Some(1)));
+    assert_eq!(v1.try_remove(10), Ok(None));
+    assert_eq!(
+        v1.try_insert(5, 9),
+        Err(TryMutError::IndexOutOfBounds { index: 5, len: 4 })
+    );
+    assert_eq!(&*v1, [0, 2, 3, 4]);
+    assert_eq!(&*v2, [1, 2, 4]);
+}
+
+#[test]
+pub fn try_clear_shared() {
+    let mut v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    v1.try_clear().unwrap();
+    assert_eq!(&*v1, []);
+    assert_eq!(&*v2, [1, 2, 3]);
+}
+
+#[test]
+pub fn try_extend_from_slice() {
+    let mut v = rc_vec![1, 2];
+    v.try_extend_from_slice(&[3, 4, 5]).unwrap();
+    assert_eq!(&*v, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+pub fn try_reserve() {
+    let mut v = rc_vec![1, 2];
+    v.try_reserve(100).unwrap();
+    let ptr = v.as_ptr();
+    for i in 0..100 {
+        v.try_push(i).unwrap();
+    }
+    assert_eq!(v.as_ptr(), ptr);
+}
+
+#[test]
+pub fn try_reserve_overflow() {
+    let mut v = rc_vec![1, 2];
+    assert_eq!(
+        v.try_reserve(usize::MAX),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert_eq!(
+        v.try_reserve_exact(usize::MAX / 2),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert_eq!(&*v, [1, 2]);
+
+    // the copy of a shared vector fails the same way
+    let v2 = v.clone();
+    assert_eq!(
+        v.try_reserve(usize::MAX),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert!(v.ptr_eq(&v2));
+}
+
+#[test]
+pub fn try_with_capacity_overflow() {
+    let v = RcVec::<u64>::try_with_capacity(usize::MAX / 4);
+    assert_eq!(v.err(), Some(TryReserveError::CapacityOverflow));
+}
+
+#[test]
+pub fn into_iter_moves_values() {
+    let v = rc_vec![Vec::from([1]), Vec::from([2])];
+    let items: Vec<_> = v.into_iter().collect();
+    assert_eq!(items, [[1], [2]]);
+}
@@ -0,0 +1,67 @@
+use crate::rc_vec;
+use crate::vec::RcVec;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+
+fn hash_of<T: Hash + ?Sized>(x: &T) -> u64 {
+    let mut h = DefaultHasher::new();
+    x.hash(&mut h);
+    h.finish()
+}
+
+#[test]
+pub fn hash_matches_slice() {
+    let v = rc_vec![1, 2, 3];
+    assert_eq!(hash_of(&v), hash_of(&[1, 2, 3][..]));
+    assert_eq!(hash_of(&v), hash_of(&alloc::vec![1, 2, 3]));
+}
+
+#[test]
+pub fn lookup_by_slice() {
+    let mut map = HashMap::new();
+    map.insert(rc_vec![1, 2], "a");
+    assert_eq!(map.get(&[1, 2][..]), Some(&"a"));
+
+    let mut map = BTreeMap::new();
+    map.insert(rc_vec![1, 2], "a");
+    assert_eq!(map.get(&[1, 2][..]), Some(&"a"));
+}
+
+#[test]
+pub fn symmetric_eq() {
+    let v: RcVec<i32> = rc_vec![1, 2];
+    let vec: Vec<i32> = alloc::vec![1, 2];
+    let slice: &[i32] = &[1, 2];
+    assert!(v == [1, 2]);
+    assert!([1, 2] == v);
+    assert!(v == vec);
+    assert!(vec == v);
+    assert!(v == slice);
+    assert!(slice == v);
+    assert!(v == *slice);
+    assert!(*slice == v);
+    assert!(v != [1]);
+    assert!([1] != v);
+}
+
+#[test]
+pub fn symmetric_ord() {
+    let v: RcVec<i32> = rc_vec![1, 2];
+    let vec: Vec<i32> = alloc::vec![1, 3];
+    assert!(v < vec);
+    assert!(vec > v);
+    assert!(v > [1, 1]);
+    assert!([1, 1] < v);
+    assert!(v <= [1, 2][..]);
+    assert!([1, 2][..] >= v);
+}
+
+#[test]
+pub fn as_ref() {
+    let v = rc_vec![1, 2];
+    let s: &[i32] = v.as_ref();
+    assert_eq!(s, [1, 2]);
+}
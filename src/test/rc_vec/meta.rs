@@ -0,0 +1,93 @@
+use crate::vec::{MetaArcVec, MetaRcVec};
+use alloc::rc::Rc;
+use alloc::string::String;
+
+#[test]
+pub fn handles_share_meta() {
+    let mut a: MetaRcVec<i32, String> = MetaRcVec::with_meta(String::from("text/plain"));
+    a.extend([1, 2, 3]);
+    let b = a.clone();
+    assert_eq!(b.meta().unwrap(), "text/plain");
+    assert!(a.meta().unwrap().as_ptr() == b.meta().unwrap().as_ptr());
+}
+
+#[test]
+pub fn meta_mut_copies_on_write() {
+    let mut a: MetaRcVec<i32, String> = MetaRcVec::with_meta(String::from("json"));
+    a.extend([1, 2, 3]);
+    let b = a.clone();
+    assert!(a.get_meta_mut().is_err());
+    a.meta_mut().push_str("; v2");
+    assert!(!a.ptr_eq(&b));
+    assert_eq!(a.meta().unwrap(), "json; v2");
+    assert_eq!(b.meta().unwrap(), "json");
+    assert_eq!(a, b);
+
+    // a copy made by mutating the elements keeps the metadata
+    let mut c = b.clone();
+    c.push(4);
+    assert_eq!(c.meta().unwrap(), "json");
+    *c.get_meta_mut().unwrap() = String::from("csv");
+    assert_eq!(b.meta().unwrap(), "json");
+}
+
+#[test]
+pub fn placeholder_has_no_meta_until_mutated() {
+    let mut v: MetaRcVec<i32, u32> = MetaRcVec::new();
+    assert_eq!(v.meta(), None);
+    *v.get_meta_mut().unwrap() = 7;
+    assert_eq!(v.meta(), Some(&7));
+    v.push(1);
+    assert_eq!(v.into_iter().collect::<alloc::vec::Vec<_>>(), [1]);
+}
+
+#[test]
+pub fn meta_is_dropped_with_vector() {
+    let tag = Rc::new(());
+    let mut v: MetaRcVec<i32, Option<Rc<()>>> = MetaRcVec::with_meta(Some(tag.clone()));
+    v.push(1);
+    let w = v.clone();
+    v.push(2);
+    assert_eq!(Rc::strong_count(&tag), 3);
+    drop(v);
+    drop(w);
+    assert_eq!(Rc::strong_count(&tag), 1);
+}
+
+#[test]
+pub fn arc_meta_is_send() {
+    fn assert_send_sync<X: Send + Sync>(_: &X) {}
+    let v: MetaArcVec<u8, u64> = MetaArcVec::with_meta(0xfeed);
+    assert_send_sync(&v);
+}
+
+fn shared_with_meta() -> (MetaRcVec<i32, u32>, MetaRcVec<i32, u32>) {
+    let mut a = MetaRcVec::with_meta(7);
+    a.extend([1, 2, 3, 4]);
+    let b = a.clone();
+    (a, b)
+}
+
+#[test]
+pub fn shared_copies_keep_meta() {
+    let (mut a, _b) = shared_with_meta();
+    a.truncate(1);
+    assert_eq!((&a[..], a.meta()), (&[1][..], Some(&7)));
+
+    let (mut a, _b) = shared_with_meta();
+    a.retain(|x| x % 2 == 0);
+    assert_eq!((&a[..], a.meta()), (&[2, 4][..], Some(&7)));
+
+    let (mut a, _b) = shared_with_meta();
+    let tail = a.split_off(3);
+    assert_eq!((&a[..], a.meta()), (&[1, 2, 3][..], Some(&7)));
+    assert_eq!((&tail[..], tail.meta()), (&[4][..], Some(&7)));
+
+    let (mut a, _b) = shared_with_meta();
+    let tail = a.make_mut().split_off(3);
+    assert_eq!(tail.freeze().meta(), Some(&7));
+
+    let (mut a, _b) = shared_with_meta();
+    a.try_clear().unwrap();
+    assert_eq!((a.len(), a.meta()), (0, Some(&7)));
+}
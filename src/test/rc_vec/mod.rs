@@ -1,3 +1,21 @@
 pub mod create;
 pub mod drop;
 pub mod rc;
+pub mod policy;
+pub mod size;
+pub mod fallible;
+pub mod unique;
+pub mod counts;
+pub mod convert;
+pub mod slice;
+pub mod small;
+pub mod vec_ops;
+pub mod keys;
+pub mod static_data;
+pub mod allocator;
+#[cfg(feature = "std")]
+pub mod cycle;
+pub mod persistent;
+pub mod segmented;
+pub mod versioned;
+pub mod meta;
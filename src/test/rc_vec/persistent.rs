@@ -0,0 +1,123 @@
+use crate::vec::PVec;
+use alloc::vec::Vec;
+
+fn to_vec(v: &PVec<u32>) -> Vec<u32> {
+    v.iter().copied().collect()
+}
+
+#[test]
+pub fn push_get_pop() {
+    let mut v = PVec::new();
+    for i in 0..5000 {
+        v.push_back(i);
+    }
+    assert_eq!(v.len(), 5000);
+    assert_eq!(v[0], 0);
+    assert_eq!(v[1234], 1234);
+    assert_eq!(v.get(5000), None);
+    assert_eq!(to_vec(&v), (0..5000).collect::<Vec<_>>());
+    for i in (0..5000).rev() {
+        assert_eq!(v.pop_back(), Some(i));
+    }
+    assert_eq!(v.pop_back(), None);
+    assert!(v.is_empty());
+}
+
+#[test]
+pub fn old_versions_unchanged() {
+    let v1: PVec<u32> = (0..1000).collect();
+    let mut v2 = v1.clone();
+    assert!(v1.ptr_eq(&v2));
+    assert_eq!(v2.set(500, 7), 500);
+    v2.push_back(1000);
+    let mut v3 = v2.clone();
+    v3.pop_back();
+    v3.pop_back();
+    assert_eq!(to_vec(&v1), (0..1000).collect::<Vec<_>>());
+    assert_eq!(v2[500], 7);
+    assert_eq!(v2.len(), 1001);
+    assert_eq!(v3.len(), 999);
+    assert_eq!(v3[500], 7);
+}
+
+#[test]
+pub fn split_and_concat() {
+    let v: PVec<u32> = (0..3000).collect();
+    for &at in &[0, 1, 31, 32, 33, 1024, 1500, 2999, 3000] {
+        let (l, r) = v.split_at(at);
+        assert_eq!(to_vec(&l), (0..at as u32).collect::<Vec<_>>());
+        assert_eq!(to_vec(&r), (at as u32..3000).collect::<Vec<_>>());
+        assert_eq!(l.concat(&r), v);
+    }
+    let small: PVec<u32> = (0..5).collect();
+    let joined = small.concat(&v).concat(&small);
+    let mut expected: Vec<u32> = (0..5).collect();
+    expected.extend(0..3000);
+    expected.extend(0..5);
+    assert_eq!(to_vec(&joined), expected);
+    assert_eq!(joined.len(), expected.len());
+}
+
+#[test]
+pub fn matches_vec_model() {
+    let mut seed = 12345u32;
+    let mut rand = move |n: usize| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 8) as usize % n
+    };
+    let mut v = PVec::new();
+    let mut model = Vec::new();
+    let mut history = Vec::new();
+    for step in 0..2000u32 {
+        match rand(6) {
+            0 | 1 => {
+                v.push_back(step);
+                model.push(step);
+            }
+            2 if !model.is_empty() => {
+                assert_eq!(v.pop_back(), model.pop());
+            }
+            3 if !model.is_empty() => {
+                let i = rand(model.len());
+                v.set(i, step);
+                model[i] = step;
+            }
+            4 => {
+                let at = rand(model.len() + 1);
+                let (l, r) = v.split_at(at);
+                v = r.concat(&l);
+                model.rotate_left(at);
+            }
+            _ => {
+                let other = v.clone();
+                v = v.concat(&other);
+                model.extend_from_within(..);
+                if model.len() > 4000 {
+                    let (l, _) = v.split_at(model.len() / 8);
+                    v = l;
+                    model.truncate(model.len() / 8);
+                }
+            }
+        }
+        assert_eq!(v.len(), model.len());
+        if step % 50 == 0 {
+            history.push((v.clone(), model.clone()));
+        }
+    }
+    for (v, model) in &history {
+        assert_eq!(&to_vec(v), model);
+    }
+}
+
+#[test]
+pub fn get_mut_copies_path() {
+    let v1: PVec<u32> = (0..2000).collect();
+    let mut v2 = v1.clone();
+    assert!(v2.is_shared_at(1500));
+    *v2.get_mut(1500).unwrap() = 7;
+    assert!(!v2.is_shared_at(1500));
+    assert!(v2.is_shared_at(0));
+    assert_eq!(v2.get_mut(2000), None);
+    assert_eq!(v1[1500], 1500);
+    assert_eq!(v2[1500], 7);
+}
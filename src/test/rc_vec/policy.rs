@@ -0,0 +1,81 @@
+use crate::vec::counters::{StrongType, StrongWeakCounter};
+use crate::vec::policy::{Policy, PolicyVec};
+use core::cell::Cell;
+use core::iter::FromIterator;
+use core::mem;
+
+struct FrozenAfterShare;
+
+impl Policy for FrozenAfterShare {
+    type Base = StrongType;
+    type State = Cell<bool>;
+
+    fn allow_mut(frozen: &Cell<bool>, _: &StrongWeakCounter) -> bool {
+        !frozen.get()
+    }
+
+    fn on_incr(frozen: &Cell<bool>, counter: &StrongWeakCounter) {
+        if !counter.unique_strong() {
+            frozen.set(true);
+        }
+    }
+}
+
+struct AtMostTwo;
+
+impl Policy for AtMostTwo {
+    type Base = StrongType;
+    type State = Cell<usize>;
+
+    fn on_incr(count: &Cell<usize>, _: &StrongWeakCounter) {
+        count.set(count.get() + 1);
+        assert!(count.get() <= 2, "too many sharers");
+    }
+
+    fn on_decr(count: &Cell<usize>, _: &StrongWeakCounter) {
+        count.set(count.get() - 1);
+    }
+}
+
+#[test]
+pub fn unshared_mutates_in_place() {
+    let mut v = PolicyVec::<FrozenAfterShare, _>::from_iter(0..4);
+    let ptr = v.as_ptr();
+    v[0] = 10;
+    assert_eq!(&*v, [10, 1, 2, 3]);
+    assert_eq!(v.as_ptr(), ptr);
+}
+
+#[test]
+pub fn frozen_after_share_copies() {
+    let mut v1 = PolicyVec::<FrozenAfterShare, _>::from_iter(0..4);
+    let v2 = v1.clone();
+    let ptr = v1.as_ptr();
+    mem::drop(v2);
+    v1[0] = 10;
+    assert_eq!(&*v1, [10, 1, 2, 3]);
+    assert_ne!(v1.as_ptr(), ptr);
+
+    // the copy starts unfrozen
+    let ptr = v1.as_ptr();
+    v1[1] = 11;
+    assert_eq!(&*v1, [10, 11, 2, 3]);
+    assert_eq!(v1.as_ptr(), ptr);
+}
+
+#[test]
+pub fn bounded_sharers_allowed() {
+    let v1 = PolicyVec::<AtMostTwo, _>::from_iter(0..4);
+    let v2 = v1.clone();
+    mem::drop(v2);
+    let v3 = v1.clone();
+    assert_eq!(v1, v3);
+}
+
+#[test]
+#[should_panic(expected = "too many sharers")]
+pub fn bounded_sharers_exceeded() {
+    let v1 = PolicyVec::<AtMostTwo, _>::from_iter(0..4);
+    let _v2 = v1.clone();
+    let _v3 = v1.clone();
+}
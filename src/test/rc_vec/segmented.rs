@@ -0,0 +1,76 @@
+use crate::rc_vec;
+use crate::vec::SegRcVec;
+use alloc::vec::Vec;
+
+#[test]
+pub fn push_get_pop() {
+    let mut v = SegRcVec::<u32, 4>::new();
+    for i in 0..10 {
+        v.push(i);
+    }
+    assert_eq!(v.len(), 10);
+    assert_eq!(v.chunk_count(), 3);
+    assert_eq!(v[9], 9);
+    assert_eq!(v.get(10), None);
+    assert_eq!(v.chunks().map(<[u32]>::len).collect::<Vec<_>>(), [4, 4, 2]);
+    assert_eq!(
+        v.iter().copied().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(v.pop(), Some(9));
+    v.truncate(5);
+    assert_eq!(v.chunk_count(), 2);
+    assert_eq!(v.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    v.clear();
+    assert!(v.is_empty());
+    assert_eq!(v.chunk_count(), 0);
+}
+
+#[test]
+pub fn write_copies_one_chunk() {
+    let v1: SegRcVec<u32, 4> = (0..12).collect();
+    let mut v2 = v1.clone();
+    assert!(v2.is_chunk_shared(1));
+    assert_eq!(v2.set(5, 100), (5, true));
+    assert_eq!(v2.set(6, 101), (6, false));
+    assert!(v2.is_chunk_shared(0));
+    assert!(!v2.is_chunk_shared(1));
+    v2[0] = 7;
+    assert_eq!(
+        v1.iter().copied().collect::<Vec<_>>(),
+        (0..12).collect::<Vec<_>>()
+    );
+    assert_eq!(v2.len(), 12);
+    assert_eq!(v2.chunks().nth(2), v1.chunks().nth(2));
+    assert_eq!(v2.chunks().next().unwrap(), [7, 1, 2, 3]);
+}
+
+#[test]
+pub fn flat_conversions() {
+    let small = rc_vec![1, 2, 3];
+    let seg = SegRcVec::<i32, 4>::from(small.clone());
+    assert!(seg.to_flat().ptr_eq(&small));
+    let big: SegRcVec<i32, 4> = SegRcVec::from(rc_vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(big.chunk_count(), 2);
+    assert_eq!(big.to_flat(), [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+pub fn write_copies_index_path_only() {
+    // 40 chunks need two levels of index nodes
+    let v1: SegRcVec<u32, 2> = (0..80).collect();
+    let mut v2 = v1.clone();
+    assert!(v2.is_chunk_shared(39));
+    assert_eq!(v2.set(78, 0), (78, true));
+    assert!(!v2.is_chunk_shared(39));
+    // chunks under index nodes off the written path are still shared with `v1`
+    assert!(v2.is_chunk_shared(0));
+    let (chunk, copied) = v2.chunk_mut(0);
+    chunk[0] = 5;
+    assert!(copied);
+    assert_eq!(v1[0], 0);
+    assert_eq!(v1[78], 78);
+    assert_eq!(v2[0], 5);
+    assert_eq!(v2[78], 0);
+    assert_eq!(v2.chunks().rev().nth(1), v1.chunks().rev().nth(1));
+}
@@ -0,0 +1,31 @@
+use crate::rc_vec;
+use crate::string::RcString;
+use crate::vec::{AcycVec, ArcVec, RcVec};
+use core::mem::size_of;
+
+#[test]
+pub fn handles_are_one_word() {
+    assert_eq!(size_of::<RcVec<u64>>(), size_of::<usize>());
+    assert_eq!(size_of::<AcycVec<u64>>(), size_of::<usize>());
+    assert_eq!(size_of::<ArcVec<u64>>(), size_of::<usize>());
+    assert_eq!(size_of::<RcString>(), size_of::<usize>());
+}
+
+#[test]
+pub fn option_handles_are_one_word() {
+    assert_eq!(size_of::<Option<RcVec<u64>>>(), size_of::<usize>());
+    assert_eq!(size_of::<Option<RcString>>(), size_of::<usize>());
+}
+
+#[test]
+pub fn reallocation_keeps_contents() {
+    let mut v = rc_vec![1, 2, 3];
+    for i in 4..100 {
+        v.push(i);
+    }
+    assert_eq!(v.len(), 99);
+    assert_eq!(v[98], 99);
+    let v2 = v.clone();
+    assert_eq!(v2.len(), 99);
+    assert_eq!(v, v2);
+}
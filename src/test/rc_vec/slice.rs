@@ -0,0 +1,85 @@
+use crate::rc_vec;
+use crate::vec::{RcSlice, RcVec};
+use alloc::vec::Vec;
+use core::mem;
+
+#[test]
+pub fn slice_shares_buffer() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let s = v.slice(1..4);
+    assert_eq!(&*s, [2, 3, 4]);
+    assert_eq!(s.as_ptr(), v[1..].as_ptr());
+    assert_eq!(v.strong_count(), 2);
+}
+
+#[test]
+pub fn slice_outlives_parent() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let s = v.slice(2..);
+    mem::drop(v);
+    assert_eq!(&*s, [3, 4, 5]);
+}
+
+#[test]
+pub fn reslice() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let s = v.slice(1..).slice(..=2).slice(1..);
+    assert_eq!(&*s, [3, 4]);
+}
+
+#[test]
+#[should_panic]
+pub fn slice_out_of_bounds() {
+    let v = rc_vec![1, 2, 3];
+    v.slice(1..4);
+}
+
+#[test]
+pub fn split_at_shared() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let (a, b) = v.split_at_shared(2);
+    assert_eq!(&*a, [1, 2]);
+    assert_eq!(&*b, [3, 4, 5]);
+}
+
+#[test]
+pub fn chunks_shared() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let chunks: Vec<RcSlice<_>> = v.chunks_shared(2).collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(&*chunks[0], [1, 2]);
+    assert_eq!(&*chunks[1], [3, 4]);
+    assert_eq!(&*chunks[2], [5]);
+    assert_eq!(v.strong_count(), 4);
+}
+
+#[test]
+pub fn mutate_shared_slice_copies_window() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let mut s = v.slice(1..3);
+    s[0] = 20;
+    assert_eq!(&*s, [20, 3]);
+    assert_eq!(&*v, [1, 2, 3, 4, 5]);
+    assert!(v.is_unique());
+}
+
+#[test]
+pub fn mutate_unique_slice_in_place() {
+    let v = rc_vec![1, 2, 3, 4, 5];
+    let mut s = v.slice(1..3);
+    let ptr = s.as_ptr();
+    mem::drop(v);
+    s[0] = 20;
+    assert_eq!(&*s, [20, 3]);
+    assert_eq!(s.as_ptr(), ptr);
+}
+
+#[test]
+pub fn into_vec() {
+    let v = rc_vec![1, 2, 3];
+    let whole: RcVec<_> = v.slice(..).into();
+    assert!(whole.ptr_eq(&v));
+    let part: RcVec<_> = v.slice(1..).into();
+    assert_eq!(&*part, [2, 3]);
+    assert!(!part.ptr_eq(&v));
+}
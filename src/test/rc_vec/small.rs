@@ -0,0 +1,71 @@
+use crate::vec::{RcVec, SmallRcVec};
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::mem;
+
+#[test]
+pub fn stays_inline() {
+    let mut v: SmallRcVec<u32> = SmallRcVec::new();
+    v.push(1);
+    v.push(2);
+    assert!(v.is_inline());
+    assert_eq!(*v, [1, 2]);
+    assert_eq!(v.pop(), Some(2));
+}
+
+#[test]
+pub fn promotes_on_growth() {
+    let mut v: SmallRcVec<u32> = (0..2).collect();
+    v.push(2);
+    assert!(!v.is_inline());
+    assert_eq!(*v, [0, 1, 2]);
+    v.truncate(1);
+    assert_eq!(*v, [0]);
+}
+
+#[test]
+pub fn clones() {
+    let mut v1: SmallRcVec<u32> = (0..2).collect();
+    let mut v2 = v1.clone();
+    v2[0] = 5;
+    assert_eq!((&*v1, &*v2), (&[0, 1][..], &[5, 1][..]));
+
+    v1.make_shared();
+    let v3 = v1.clone();
+    let shared: RcVec<u32> = v1.into_shared();
+    assert_eq!(shared.strong_count(), 2);
+    assert_eq!(*v3, [0, 1]);
+}
+
+#[test]
+pub fn drops_inline_elements() {
+    #[derive(Clone)]
+    struct D<'a>(&'a Cell<u32>);
+    impl Drop for D<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let count = Cell::new(0);
+    let mut v: SmallRcVec<D> = SmallRcVec::new();
+    v.push(D(&count));
+    v.push(D(&count));
+    v.truncate(1);
+    assert_eq!(count.get(), 1);
+    mem::drop(v);
+    assert_eq!(count.get(), 2);
+
+    let mut v: SmallRcVec<D, 1> = SmallRcVec::new();
+    v.push(D(&count));
+    v.push(D(&count));
+    mem::drop(v);
+    assert_eq!(count.get(), 4);
+}
+
+#[test]
+pub fn collect() {
+    let v: SmallRcVec<u32, 4> = (0..10).collect();
+    let items: Vec<u32> = v.iter().copied().collect();
+    assert_eq!(items, (0..10).collect::<Vec<_>>());
+}
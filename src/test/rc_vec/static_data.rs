@@ -0,0 +1,100 @@
+use crate::vec::{ArcVec, RcVec, UniqueRcVec};
+use alloc::vec::Vec;
+
+const EMPTY: RcVec<i32> = RcVec::new();
+static SHARED_EMPTY: ArcVec<i32> = ArcVec::new();
+static DATA: &[i32] = &[1, 2, 3];
+static NESTED: &[Vec<i32>] = &[Vec::new(), Vec::new()];
+const BORROWED: RcVec<i32> = RcVec::from_static(&(&[1, 2, 3] as &[i32]));
+
+#[test]
+pub fn const_empty() {
+    let mut v = EMPTY;
+    assert_eq!(&*v, [0i32; 0]);
+    assert!(v.is_unique());
+    assert_eq!(v.strong_count(), 1);
+    v.push(1);
+    assert_eq!(&*v, [1]);
+    assert_eq!(&*EMPTY, [0i32; 0]);
+    assert_eq!(&*SHARED_EMPTY, [0i32; 0]);
+}
+
+#[test]
+pub fn empty_clones_are_independent() {
+    let mut v1 = RcVec::<i32>::new();
+    let mut v2 = v1.clone();
+    v1.push(1);
+    v2.push(2);
+    assert_eq!((&*v1, &*v2), (&[1][..], &[2][..]));
+
+    let mut u = UniqueRcVec::new();
+    u.push(3);
+    assert_eq!(&*u, [3]);
+}
+
+#[test]
+pub fn empty_unwraps() {
+    assert_eq!(RcVec::<i32>::new().try_unwrap(), Ok(Vec::new()));
+    assert_eq!(RcVec::<i32>::new().into_inner(), Some(Vec::new()));
+    assert!(RcVec::<i32>::new().try_into_unique().is_ok());
+    assert!(RcVec::<i32>::new().downgrade().upgrade().is_none());
+
+    let mut v = RcVec::<i32>::new();
+    v.try_reserve(10).unwrap();
+    assert!(v.capacity() >= 10);
+}
+
+#[test]
+pub fn empty_weak_never_upgrades() {
+    let v = RcVec::<i32>::new();
+    let w = v.downgrade();
+    assert!(w.upgrade().is_none());
+    assert_eq!(w.strong_count(), 0);
+    drop(v);
+    assert!(w.upgrade().is_none());
+
+    let a = ArcVec::<i32>::new();
+    assert!(a.downgrade().upgrade().is_none());
+}
+
+#[test]
+pub fn from_static_borrows() {
+    let v = RcVec::from_static(&DATA);
+    assert_eq!(v.as_ptr(), DATA.as_ptr());
+    assert_eq!(&*v, [1, 2, 3]);
+
+    // static data is not counted
+    let v2 = v.clone();
+    assert_eq!(v2.strong_count(), 1);
+    assert!(v2.ptr_eq(&v));
+}
+
+#[test]
+pub fn from_static_copies_on_write() {
+    let mut v = RcVec::from_static(&DATA);
+    assert!(!v.is_unique());
+    assert!(v.unique_mut().is_err());
+    v.push(4);
+    assert_ne!(v.as_ptr(), DATA.as_ptr());
+    assert_eq!(&*v, [1, 2, 3, 4]);
+    assert_eq!(DATA, [1, 2, 3]);
+    assert_eq!(&*BORROWED, [1, 2, 3]);
+}
+
+#[test]
+pub fn from_static_weak() {
+    let v = RcVec::from_static(&DATA);
+    let w = v.downgrade();
+    assert_eq!(w.upgrade().as_deref(), Some(&[1, 2, 3][..]));
+    drop(v);
+    // static data is never freed
+    assert_eq!(w.upgrade().as_deref(), Some(&[1, 2, 3][..]));
+}
+
+#[test]
+pub fn from_static_unwrap_clones() {
+    let v = RcVec::from_static(&NESTED);
+    assert!(v.clone().into_inner().is_none());
+    assert!(v.clone().try_unwrap().is_err());
+    assert_eq!(v.unwrap_or_clone(), Vec::from([Vec::new(), Vec::new()]));
+}
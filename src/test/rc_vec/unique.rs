@@ -0,0 +1,83 @@
+use crate::error::NotUnique;
+use crate::vec::{RcVec, UniqueRcVec};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+#[derive(Debug)]
+struct NoClone(i32);
+
+#[test]
+pub fn build_non_clone() {
+    let mut u = UniqueRcVec::new();
+    u.push(NoClone(1));
+    u.push(NoClone(3));
+    u.insert(1, NoClone(2));
+    u[0].0 = 0;
+    let v: RcVec<NoClone> = u.freeze();
+    let items: Vec<i32> = v.iter().map(|x| x.0).collect();
+    assert_eq!(items, [0, 2, 3]);
+}
+
+#[test]
+pub fn freeze_does_not_copy() {
+    let u: UniqueRcVec<_> = (0..10).collect();
+    let ptr = u.as_ptr();
+    let v = u.freeze();
+    assert_eq!(v.as_ptr(), ptr);
+}
+
+#[test]
+pub fn boxed_closures() {
+    let mut u = UniqueRcVec::<Box<dyn Fn(i32) -> i32>>::new();
+    u.push(Box::new(|x| x + 1));
+    u.push(Box::new(|x| x * 2));
+    let v = u.freeze();
+    assert_eq!(v.iter().fold(3, |acc, f| f(acc)), 8);
+}
+
+#[test]
+pub fn get_mut_unique() {
+    let mut v: RcVec<_> = (0..3).map(NoClone).collect();
+    v.get_mut().unwrap()[1] = NoClone(10);
+    assert_eq!(v[1].0, 10);
+}
+
+#[test]
+pub fn get_mut_shared() {
+    let mut v1: RcVec<_> = (0..3).map(NoClone).collect();
+    let v2 = v1.clone();
+    assert!(v1.get_mut().is_err());
+    drop(v2);
+    assert!(v1.get_mut().is_ok());
+}
+
+#[test]
+pub fn get_mut_with_weak() {
+    let mut v: RcVec<_> = (0..3).map(NoClone).collect();
+    let w = v.downgrade();
+    assert!(v.get_mut().is_err());
+    drop(w);
+    assert!(v.get_mut().is_ok());
+}
+
+#[test]
+pub fn unique_mut() {
+    let mut v: RcVec<_> = (0..3).map(NoClone).collect();
+    v.unique_mut().unwrap().push(NoClone(3));
+    assert_eq!(v.unique_mut().unwrap().pop().map(|x| x.0), Some(3));
+    let v2 = v.clone();
+    assert_eq!(v.unique_mut().err(), Some(NotUnique));
+    assert_eq!(v2.len(), 3);
+}
+
+#[test]
+pub fn try_into_unique() {
+    let v1: RcVec<_> = (0..3).map(NoClone).collect();
+    let v2 = v1.clone();
+    let v1 = v1.try_into_unique().unwrap_err();
+    drop(v2);
+    let mut u = v1.try_into_unique().unwrap();
+    u.truncate(1);
+    let items: Vec<i32> = u.into_iter().map(|x| x.0).collect();
+    assert_eq!(items, [0]);
+}
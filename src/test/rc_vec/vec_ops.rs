@@ -0,0 +1,151 @@
+use crate::rc_vec;
+use crate::vec::{RcVec, UniqueRcVec};
+use alloc::vec::Vec;
+
+#[test]
+pub fn truncate_shared_copies_prefix() {
+    let mut v1 = rc_vec![1, 2, 3, 4];
+    let v2 = v1.clone();
+    v1.truncate(2);
+    assert_eq!(v1, rc_vec![1, 2]);
+    assert_eq!(v2, rc_vec![1, 2, 3, 4]);
+    v1.truncate(5);
+    assert_eq!(v1.len(), 2);
+}
+
+#[test]
+pub fn resize() {
+    let mut v: RcVec<i32> = rc_vec![1];
+    let v2 = v.clone();
+    v.resize(3, 7);
+    assert_eq!(v, rc_vec![1, 7, 7]);
+    let mut n = 0;
+    v.resize_with(5, || {
+        n += 1;
+        n
+    });
+    assert_eq!(v, rc_vec![1, 7, 7, 1, 2]);
+    v.resize(1, 0);
+    assert_eq!(v, v2);
+}
+
+#[test]
+pub fn retain() {
+    let mut v1: RcVec<i32> = (0..10).collect();
+    let v2 = v1.clone();
+    v1.retain(|x| x % 3 == 0);
+    assert_eq!(v1, rc_vec![0, 3, 6, 9]);
+    assert_eq!(v2.len(), 10);
+
+    v1.retain_mut(|x| {
+        *x += 1;
+        *x > 1
+    });
+    assert_eq!(v1, rc_vec![4, 7, 10]);
+}
+
+#[test]
+pub fn dedup_by_key() {
+    let mut v = rc_vec![1, 1, 2, 3, 3, 3, 1, 4, 4];
+    v.dedup_by_key(|x| *x);
+    assert_eq!(v, rc_vec![1, 2, 3, 1, 4]);
+
+    let mut v = rc_vec![10, 11, 20, 25, 31];
+    v.dedup_by_key(|x| *x / 10);
+    assert_eq!(v, rc_vec![10, 20, 31]);
+}
+
+#[test]
+pub fn drain() {
+    let mut v1: RcVec<i32> = (0..6).collect();
+    let v2 = v1.clone();
+    let drained: Vec<_> = v1.drain(1..4).collect();
+    assert_eq!(drained, [1, 2, 3]);
+    assert_eq!(v1, rc_vec![0, 4, 5]);
+    assert_eq!(v2.len(), 6);
+    assert_eq!(v1.drain(..).len(), 3);
+    assert!(v1.is_empty());
+}
+
+#[test]
+pub fn splice() {
+    let mut v = rc_vec![1, 2, 3, 4];
+    let removed: Vec<_> = v.splice(1..3, [7, 8, 9]).collect();
+    assert_eq!(removed, [2, 3]);
+    assert_eq!(v, rc_vec![1, 7, 8, 9, 4]);
+    v.splice(..0, [0]);
+    assert_eq!(v, rc_vec![0, 1, 7, 8, 9, 4]);
+}
+
+#[test]
+pub fn split_off() {
+    let mut v1: RcVec<i32> = (0..5).collect();
+    let tail = v1.split_off(3);
+    assert_eq!((v1.clone(), tail), (rc_vec![0, 1, 2], rc_vec![3, 4]));
+
+    let v2 = v1.clone();
+    let tail = v1.split_off(1);
+    assert_eq!((&v1, &tail), (&rc_vec![0], &rc_vec![1, 2]));
+    assert_eq!(v2, rc_vec![0, 1, 2]);
+}
+
+#[test]
+pub fn append() {
+    let mut v1 = rc_vec![1, 2];
+    let mut v2 = rc_vec![3, 4];
+    let v3 = v2.clone();
+    v1.append(&mut v2);
+    assert_eq!(v1, rc_vec![1, 2, 3, 4]);
+    assert!(v2.is_empty());
+    assert_eq!(v3, rc_vec![3, 4]);
+
+    let mut u1: UniqueRcVec<_> = (0..2).collect();
+    let mut u2: UniqueRcVec<_> = (2..4).collect();
+    u1.append(&mut u2);
+    assert_eq!(*u1, [0, 1, 2, 3]);
+    assert!(u2.is_empty());
+}
+
+#[test]
+pub fn swap_remove() {
+    let mut v = rc_vec![1, 2, 3, 4];
+    assert_eq!(v.swap_remove(0), Some(1));
+    assert_eq!(v, rc_vec![4, 2, 3]);
+}
+
+#[test]
+pub fn extend_from_within() {
+    let mut v1 = rc_vec![1, 2, 3];
+    let v2 = v1.clone();
+    v1.extend_from_within(1..);
+    assert_eq!(v1, rc_vec![1, 2, 3, 2, 3]);
+    assert_eq!(v2, rc_vec![1, 2, 3]);
+}
+
+#[test]
+pub fn capacity() {
+    let mut v1: RcVec<i32> = RcVec::with_capacity(4);
+    v1.push(1);
+    let v2 = v1.clone();
+    v1.reserve_exact(10);
+    assert!(v1.capacity() >= 11);
+    assert!(!v1.ptr_eq(&v2));
+
+    v1.shrink_to_fit();
+    assert_eq!(v1.capacity(), 1);
+
+    let v3 = v1.clone();
+    v1.reserve(100);
+    assert!(v1.capacity() >= 101);
+    assert_eq!(v3.capacity(), 1);
+}
+
+#[test]
+pub fn extend() {
+    let mut v = rc_vec![1];
+    let v2 = v.clone();
+    v.extend(2..4);
+    v.extend(&[4, 5]);
+    assert_eq!(v, rc_vec![1, 2, 3, 4, 5]);
+    assert_eq!(v2, rc_vec![1]);
+}
@@ -0,0 +1,50 @@
+use crate::vec::VersionedRcVec;
+
+#[test]
+pub fn mutation_bumps_version() {
+    let mut v: VersionedRcVec<i32> = VersionedRcVec::from(vec![1, 2, 3]);
+    let v0 = v.version();
+    assert!(!v.changed_since(v0));
+    let _ = v.len();
+    assert!(!v.changed_since(v0));
+    v.push(4);
+    let v1 = v.version();
+    assert!(v.changed_since(v0));
+    v[0] = 5;
+    assert!(v.changed_since(v1));
+}
+
+#[test]
+pub fn shared_handles_see_same_version() {
+    let mut a: VersionedRcVec<i32> = VersionedRcVec::from(vec![1, 2, 3]);
+    a.push(4);
+    let b = a.clone();
+    assert_eq!(a.version(), b.version());
+    let seen = b.version();
+    // copy-on-write gives `a` a buffer of its own, leaving `b` unchanged
+    a.push(5);
+    assert!(a.changed_since(seen));
+    assert!(!b.changed_since(seen));
+    assert_ne!(a.version(), b.version());
+}
+
+#[test]
+pub fn copy_starts_new_lineage() {
+    let a: VersionedRcVec<i32> = VersionedRcVec::from(vec![1]);
+    let mut b = a.clone();
+    let seen = a.version();
+    b.get_mut().unwrap_err();
+    b.make_mut();
+    // the copy has a different lineage, whatever its count
+    assert!(b.changed_since(seen));
+    assert!(!a.changed_since(seen));
+}
+
+#[test]
+pub fn empty_placeholder_changes_on_first_mutation() {
+    let mut v = VersionedRcVec::<i32>::new();
+    let v0 = v.version();
+    assert_eq!(v0, VersionedRcVec::<i32>::new().version());
+    v.push(1);
+    assert!(v.changed_since(v0));
+}
@@ -0,0 +1,18 @@
+use crate::string::RcString;
+use crate::test::rc_vec::allocator::Tracker;
+
+#[test]
+pub fn string_allocates_in_allocator() {
+    let tracker = Tracker::default();
+    let mut s = RcString::from_str_in("foo", &tracker);
+    let s2 = s.clone();
+    s.push_str("bar");
+    s.retain(|c| c != 'o');
+    assert_eq!(s, "fbar");
+    assert_eq!(s2, "foo");
+    assert_eq!(tracker.live.get(), 2);
+    assert!(core::ptr::eq(*s.split_off(1).allocator(), &tracker));
+    drop(s);
+    drop(s2);
+    assert_eq!(tracker.live.get(), 0);
+}
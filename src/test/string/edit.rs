@@ -0,0 +1,123 @@
+use crate::rc_str;
+use crate::string::RcString;
+use alloc::string::String;
+
+#[test]
+pub fn insert_remove() {
+    let mut s1 = rc_str!("hllo");
+    let s2 = s1.clone();
+    s1.insert(1, 'e');
+    s1.insert_str(5, ", wörld");
+    assert_eq!(s1, "hello, wörld");
+    assert_eq!(s2, "hllo");
+    assert_eq!(s1.remove(8), 'ö');
+    assert_eq!(s1.pop(), Some('d'));
+    assert_eq!(s1, "hello, wrl");
+}
+
+#[test]
+#[should_panic]
+pub fn insert_not_char_boundary() {
+    let mut s = rc_str!("ö");
+    s.insert(1, 'a');
+}
+
+#[test]
+pub fn truncate() {
+    let mut s1 = rc_str!("añb");
+    let s2 = s1.clone();
+    s1.truncate(3);
+    assert_eq!(s1, "añ");
+    s1.truncate(10);
+    assert_eq!(s1, "añ");
+    assert_eq!(s2, "añb");
+}
+
+#[test]
+#[should_panic]
+pub fn truncate_not_char_boundary() {
+    rc_str!("añb").truncate(2);
+}
+
+#[test]
+pub fn retain() {
+    let mut s1 = rc_str!("a-ñ-b-ç");
+    let s2 = s1.clone();
+    s1.retain(|c| c != '-');
+    assert_eq!(s1, "añbç");
+    assert_eq!(s2, "a-ñ-b-ç");
+    s1.retain(|c| c.is_ascii());
+    assert_eq!(s1, "ab");
+}
+
+#[test]
+pub fn retain_panic_keeps_valid_utf8() {
+    extern crate std;
+    let mut s = rc_str!("ñañbñ");
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        s.retain(|c| if c == 'b' { panic!() } else { c != 'a' })
+    }));
+    assert!(res.is_err());
+    assert_eq!(s, "ññbñ");
+}
+
+#[test]
+pub fn drain() {
+    let mut s = rc_str!("héllo wörld");
+    let d: String = s.drain(..7).collect();
+    assert_eq!(d, "héllo ");
+    assert_eq!(s, "wörld");
+    let mut d = s.drain(1..);
+    assert_eq!(d.next_back(), Some('d'));
+    assert_eq!(d.as_str(), "örl");
+}
+
+#[test]
+pub fn replace_range() {
+    let mut s = rc_str!("hello world");
+    s.replace_range(6.., "there");
+    assert_eq!(s, "hello there");
+    s.replace_range(..5, "hi");
+    assert_eq!(s, "hi there");
+}
+
+#[test]
+pub fn split_off() {
+    let mut s1 = rc_str!("hello world");
+    let s2 = s1.split_off(5);
+    assert_eq!((s1.as_str(), s2.as_str()), ("hello", " world"));
+}
+
+#[test]
+pub fn capacity() {
+    let mut s1 = RcString::with_capacity(10);
+    assert!(s1.capacity() >= 10);
+    s1.push_str("abc");
+    let s2 = s1.clone();
+    s1.reserve(20);
+    assert!(s1.capacity() >= 23);
+    assert!(!s1.ptr_eq(&s2));
+    s1.clear();
+    assert!(s1.is_empty());
+}
+
+#[test]
+pub fn bytes() {
+    let s = rc_str!("abc");
+    assert_eq!(s.as_bytes(), b"abc");
+    let ptr = s.as_ptr();
+    let v = s.into_bytes();
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(*v, *b"abc");
+}
+
+#[test]
+pub fn ascii_case() {
+    let mut s1 = rc_str!("ABC");
+    let s2 = s1.clone();
+    s1.make_ascii_uppercase();
+    assert!(s1.ptr_eq(&s2));
+    s1.make_ascii_lowercase();
+    assert_eq!(s1, "abc");
+    assert_eq!(s2, "ABC");
+}
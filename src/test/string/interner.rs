@@ -0,0 +1,48 @@
+use crate::string::{ArcInterner, Interner};
+use alloc::vec::Vec;
+use core::mem;
+
+#[test]
+pub fn intern_dedups() {
+    let mut i = Interner::new();
+    let a = i.intern("foo");
+    let b = i.intern("foo");
+    let c = i.intern("bar");
+    assert!(a.ptr_eq(&b));
+    assert!(!a.ptr_eq(&c));
+    assert_eq!(i.len(), 2);
+    assert!(i.get("foo").unwrap().ptr_eq(&a));
+    assert!(i.get("baz").is_none());
+}
+
+#[test]
+pub fn gc_evicts_unused() {
+    let mut i = Interner::new();
+    let a = i.intern("foo");
+    let b = i.intern("bar");
+    mem::drop(b);
+    assert_eq!(i.gc(), 1);
+    assert_eq!(i.len(), 1);
+    assert!(i.contains("foo"));
+    assert!(!i.contains("bar"));
+    mem::drop(a);
+    assert_eq!(i.gc(), 1);
+    assert!(i.is_empty());
+}
+
+#[test]
+pub fn iter_sorted() {
+    let i: Interner = ["b", "c", "a", "b"].iter().copied().collect();
+    let items: Vec<&str> = i.iter().map(|s| s.as_str()).collect();
+    assert_eq!(items, ["a", "b", "c"]);
+}
+
+#[test]
+pub fn arc_interner_across_threads() {
+    extern crate std;
+    let mut i = ArcInterner::new();
+    let a = i.intern("foo");
+    let t = std::thread::spawn(move || a.len());
+    assert_eq!(t.join().unwrap(), 3);
+    assert_eq!(i.gc(), 1);
+}
@@ -0,0 +1,69 @@
+use crate::rc_str;
+use crate::string::{RcStr, RcString};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+
+fn hash_of<T: Hash + ?Sized>(x: &T) -> u64 {
+    let mut h = DefaultHasher::new();
+    x.hash(&mut h);
+    h.finish()
+}
+
+#[test]
+pub fn hash_matches_str() {
+    let s = rc_str!("foo");
+    assert_eq!(hash_of(&s), hash_of("foo"));
+    assert_eq!(hash_of(&s), hash_of(&String::from("foo")));
+    assert_eq!(hash_of(&RcStr::from(s)), hash_of("foo"));
+}
+
+#[test]
+pub fn lookup_by_str() {
+    let mut map = HashMap::new();
+    map.insert(rc_str!("foo"), 1);
+    assert_eq!(map.get("foo"), Some(&1));
+
+    let mut map = BTreeMap::new();
+    map.insert(RcStr::from(rc_str!("foo")), 1);
+    assert_eq!(map.get("foo"), Some(&1));
+}
+
+#[test]
+pub fn symmetric_eq() {
+    let s: RcString = rc_str!("foo");
+    let string = String::from("foo");
+    assert!(s == "foo");
+    assert!("foo" == s);
+    assert!(s == *"foo");
+    assert!(*"foo" == s);
+    assert!(s == string);
+    assert!(string == s);
+
+    let sub = s.substr(1..);
+    assert!(sub == "oo");
+    assert!("oo" == sub);
+    let string = String::from("oo");
+    assert!(string == sub);
+}
+
+#[test]
+pub fn symmetric_ord() {
+    let s: RcString = rc_str!("b");
+    assert!(s > "a");
+    assert!("a" < s);
+    assert!(s < *"c");
+    assert!(*"c" > s);
+    let string = String::from("c");
+    assert!(string > s);
+    assert!("c" > s.substr(..));
+}
+
+#[test]
+pub fn as_ref_bytes() {
+    let s = rc_str!("foo");
+    let b: &[u8] = s.as_ref();
+    assert_eq!(b, b"foo");
+}
@@ -86,3 +86,17 @@ pub fn display() {
     let s = rc_str!("foo\nbar");
     assert_eq!(format!("{}", s), "foo\nbar")
 }
+
+#[test]
+pub fn ptr_eq() {
+    let s1 = rc_str!("foo");
+    let s2 = s1.clone();
+    let s3 = rc_str!("foo");
+    assert!(s1.ptr_eq(&s2));
+    assert!(!s1.ptr_eq(&s3));
+    assert_eq!(s1, s2);
+    assert_eq!(s1, s3);
+    assert_eq!(s1.strong_count(), 2);
+    assert!(!s1.is_unique());
+    assert!(s3.is_unique());
+}
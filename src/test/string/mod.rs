@@ -1,3 +1,13 @@
 pub mod create;
+pub mod edit;
+pub mod keys;
 pub mod misc;
+pub mod small;
 pub mod from_iter;
+pub mod interner;
+pub mod substr;
+pub mod weak;
+pub mod utf;
+pub mod static_data;
+pub mod allocator;
+pub mod rope;
@@ -0,0 +1,87 @@
+use crate::string::{RcRope, RcString};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+fn text() -> String {
+    let mut s = String::new();
+    for i in 0..500 {
+        s.push_str(&format!("line {} — ünïcödé\n", i));
+    }
+    s
+}
+
+#[test]
+pub fn round_trip() {
+    let s = text();
+    let rope = RcRope::from(RcString::from(&*s));
+    assert_eq!(rope.len(), s.len());
+    assert_eq!(rope.len_chars(), s.chars().count());
+    assert_eq!(rope.len_lines(), 501);
+    assert!(rope.chunks().count() > 1);
+    assert_eq!(rope.to_string(), s);
+    assert_eq!(rope.to_flat(), &*s);
+    assert_eq!(rope, &*s);
+}
+
+#[test]
+pub fn edits_keep_snapshots() {
+    let s = text();
+    let mut rope = RcRope::from(&*s);
+    let mut model = s.clone();
+    let mut snapshots = Vec::new();
+    for i in 0..200 {
+        let at = model
+            .char_indices()
+            .nth(i * 37 % model.chars().count())
+            .unwrap()
+            .0;
+        rope.insert(at, "ab");
+        model.insert_str(at, "ab");
+        if i % 3 == 0 {
+            rope.remove(at..at + 1);
+            model.replace_range(at..at + 1, "");
+        }
+        snapshots.push((rope.clone(), model.clone()));
+    }
+    for (rope, model) in &snapshots {
+        assert_eq!(rope.to_string(), *model);
+    }
+}
+
+#[test]
+pub fn slice_and_concat() {
+    let s = text();
+    let rope = RcRope::from(&*s);
+    let start = s.char_indices().nth(1000).unwrap().0;
+    let end = s.char_indices().nth(5000).unwrap().0;
+    let slice = rope.slice(start..end);
+    assert_eq!(slice.to_string(), &s[start..end]);
+    let (l, r) = rope.split_at(start);
+    assert_eq!(
+        r.concat(&l).to_string(),
+        format!("{}{}", &s[start..], &s[..start])
+    );
+    assert_eq!(l.concat(&r), rope);
+}
+
+#[test]
+pub fn indexing() {
+    let s = text();
+    let rope = RcRope::from(&*s);
+    for (ci, (bi, c)) in s.char_indices().enumerate().step_by(97) {
+        assert_eq!(rope.char(ci), Some(c));
+        assert_eq!(rope.char_to_byte(ci), bi);
+        assert_eq!(rope.byte_to_char(bi), ci);
+        assert_eq!(rope.byte_to_line(bi), s[..bi].matches('\n').count());
+    }
+    assert_eq!(rope.char(rope.len_chars()), None);
+    assert_eq!(rope.char_to_byte(rope.len_chars()), s.len());
+    for (i, line) in s.split_inclusive('\n').enumerate().step_by(13) {
+        assert_eq!(rope.line(i), line);
+        assert_eq!(
+            rope.line_to_byte(i),
+            s.lines().take(i).map(|l| l.len() + 1).sum::<usize>()
+        );
+    }
+    assert_eq!(rope.line(500), "");
+}
@@ -0,0 +1,39 @@
+use crate::string::small::INLINE_CAP;
+use crate::string::{RcString, SmallRcString};
+use core::mem;
+
+#[test]
+pub fn stays_inline() {
+    let mut s = SmallRcString::from("foo");
+    s.push('ß');
+    assert!(s.is_inline());
+    assert_eq!(s, "fooß");
+    assert_eq!(s.pop(), Some('ß'));
+    assert_eq!(mem::size_of::<SmallRcString>(), 3 * mem::size_of::<usize>());
+}
+
+#[test]
+pub fn promotes_on_growth() {
+    let long = "x".repeat(INLINE_CAP);
+    let mut s = SmallRcString::from(long.as_str());
+    assert!(s.is_inline());
+    s.push('y');
+    assert!(!s.is_inline());
+    assert_eq!(s.len(), INLINE_CAP + 1);
+    s.truncate(1);
+    assert_eq!(s, "x");
+}
+
+#[test]
+pub fn shared_clones() {
+    let mut s1 = SmallRcString::from("foo");
+    let mut s2 = s1.clone();
+    s2.make_ascii_uppercase();
+    assert_eq!((s1.as_str(), s2.as_str()), ("foo", "FOO"));
+
+    s1.make_shared();
+    let s3 = s1.clone();
+    let shared: RcString = s1.into_shared();
+    assert_eq!(shared.strong_count(), 2);
+    assert_eq!(s3, "foo");
+}
@@ -0,0 +1,32 @@
+use crate::string::{ArcString, RcString};
+use std::thread;
+
+const EMPTY: RcString = RcString::new();
+
+#[test]
+pub fn const_empty() {
+    let mut s = EMPTY;
+    assert_eq!(s, "");
+    s.push_str("foo");
+    assert_eq!(s, "foo");
+}
+
+#[test]
+pub fn from_static() {
+    const TEXT: &str = "Hello";
+    let mut s = RcString::from_static(&TEXT);
+    assert_eq!(s.as_ptr(), TEXT.as_ptr());
+    let s2 = s.clone();
+    s.make_ascii_uppercase();
+    assert_eq!((s.as_str(), s2.as_str()), ("HELLO", "Hello"));
+    assert_eq!(s2.as_ptr(), TEXT.as_ptr());
+}
+
+#[test]
+pub fn from_static_across_threads() {
+    let s = ArcString::from_static(&"shared");
+    let s2 = s.clone();
+    let len = thread::spawn(move || s2.len()).join().unwrap();
+    assert_eq!(len, 6);
+    assert_eq!(s.strong_count(), 1);
+}
@@ -0,0 +1,93 @@
+use crate::rc_str;
+use crate::string::{RcStr, RcString};
+use alloc::vec::Vec;
+use core::mem;
+
+#[test]
+pub fn substr_shares_buffer() {
+    let s = rc_str!("hello, world");
+    let sub = s.substr(7..);
+    assert_eq!(sub, "world");
+    assert_eq!(sub.as_ptr(), s[7..].as_ptr());
+    assert_eq!(s.strong_count(), 2);
+}
+
+#[test]
+pub fn substr_outlives_parent() {
+    let s = rc_str!("hello, world");
+    let sub = s.substr(..5);
+    mem::drop(s);
+    assert_eq!(sub, "hello");
+}
+
+#[test]
+pub fn nested_substr() {
+    let s = rc_str!("hello, world");
+    let sub = s.substr(7..).substr(1..3);
+    assert_eq!(sub, "or");
+}
+
+#[test]
+#[should_panic(expected = "char boundaries")]
+pub fn substr_not_on_boundary() {
+    let s = RcString::from("héllo");
+    s.substr(..2);
+}
+
+#[test]
+pub fn split_shared() {
+    let s = rc_str!("a,bc,,d");
+    let parts: Vec<RcStr> = s.split_shared(",").collect();
+    assert_eq!(parts, ["a", "bc", "", "d"]);
+    assert_eq!(parts[1].as_ptr(), s[2..].as_ptr());
+}
+
+#[test]
+pub fn lines_shared() {
+    let s = rc_str!("one\ntwo\r\nthree");
+    let lines: Vec<RcStr> = s.lines_shared().collect();
+    assert_eq!(lines, ["one", "two", "three"]);
+}
+
+#[test]
+pub fn split_whitespace_shared() {
+    let s = rc_str!("  let x =\t1;\n");
+    let tokens: Vec<RcStr> = s.split_whitespace_shared().collect();
+    assert_eq!(tokens, ["let", "x", "=", "1;"]);
+    let rev: Vec<RcStr> = s.split_whitespace_shared().rev().collect();
+    assert_eq!(rev, ["1;", "=", "x", "let"]);
+}
+
+#[test]
+pub fn trim_shared() {
+    let s = rc_str!("  padded  ");
+    let t = s.trim_shared();
+    assert_eq!(t, "padded");
+    assert_eq!(t.trim_shared(), "padded");
+}
+
+#[test]
+pub fn tokens_of_substr() {
+    let s = rc_str!("a b c d");
+    let tokens: Vec<RcStr> = s.substr(2..).split_whitespace_shared().collect();
+    assert_eq!(tokens, ["b", "c", "d"]);
+}
+
+#[test]
+pub fn into_string() {
+    let s = rc_str!("hello, world");
+    let whole: RcString = s.substr(..).into();
+    assert!(whole.ptr_eq(&s));
+    let part: RcString = s.substr(7..).into();
+    assert_eq!(part, "world");
+    assert!(!part.ptr_eq(&s));
+}
+
+#[test]
+pub fn mutate_substr_copies() {
+    let s = rc_str!("hello, world");
+    let mut sub = s.substr(7..);
+    sub.make_ascii_uppercase();
+    assert_eq!(sub, "WORLD");
+    assert_eq!(s, "hello, world");
+}
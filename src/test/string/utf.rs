@@ -0,0 +1,56 @@
+use crate::rc_vec;
+use crate::string::{RcString, StrongRcString};
+use crate::vec::AcycVec;
+
+#[test]
+pub fn from_utf8() {
+    let v: AcycVec<u8> = AcycVec::copy_from_slice("héllo".as_bytes());
+    let ptr = v.as_ptr();
+    let s = RcString::from_utf8(v).unwrap();
+    assert_eq!(s, "héllo");
+    assert_eq!(s.as_ptr(), ptr);
+}
+
+#[test]
+pub fn from_utf8_returns_buffer() {
+    let v: AcycVec<u8> = AcycVec::copy_from_slice(b"ab\xffc");
+    let v2 = v.clone();
+    let err = RcString::from_utf8(v).unwrap_err();
+    assert_eq!(err.utf8_error().valid_up_to(), 2);
+    assert!(err.into_bytes().ptr_eq(&v2));
+}
+
+#[test]
+pub fn from_utf8_unchecked() {
+    let v: AcycVec<u8> = AcycVec::copy_from_slice(b"abc");
+    let s = unsafe { RcString::from_utf8_unchecked(v) };
+    assert_eq!(s, "abc");
+}
+
+#[test]
+pub fn from_utf8_lossy() {
+    let v: AcycVec<u8> = AcycVec::copy_from_slice(b"abc");
+    let ptr = v.as_ptr();
+    let s = RcString::from_utf8_lossy(v);
+    assert_eq!(s.as_ptr(), ptr);
+
+    let v: AcycVec<u8> = AcycVec::copy_from_slice(b"a\xffc");
+    assert_eq!(RcString::from_utf8_lossy(v), "a\u{fffd}c");
+}
+
+#[test]
+pub fn from_utf16() {
+    let v = [0x68, 0xd834, 0xdd1e, 0x69];
+    assert_eq!(RcString::from_utf16(&v).unwrap(), "h\u{1d11e}i");
+    let v = [0x68, 0xd834, 0x69];
+    assert!(RcString::from_utf16(&v).is_err());
+    assert_eq!(RcString::from_utf16_lossy(&v), "h\u{fffd}i");
+}
+
+#[test]
+pub fn from_rc_vec() {
+    let v = rc_vec![b'a', b'b'];
+    let s = StrongRcString::from_utf8(v.clone()).unwrap();
+    assert_eq!(s, "ab");
+    assert_eq!(s.as_ptr(), v.as_ptr());
+}
@@ -0,0 +1,49 @@
+use crate::string::{StrongArcString, StrongRcString};
+use alloc::format;
+use core::mem;
+
+#[test]
+pub fn downgrade_upgrade() {
+    let s1 = StrongRcString::from("foo");
+    let w = s1.downgrade();
+    let s2 = w.upgrade().unwrap();
+    assert_eq!(s2, "foo");
+    assert!(s1.ptr_eq(&s2));
+    assert_eq!((w.strong_count(), w.weak_count()), (2, 1));
+}
+
+#[test]
+pub fn upgrade_should_fail() {
+    let s = StrongRcString::from("foo");
+    let w = s.downgrade();
+    mem::drop(s);
+    assert!(w.upgrade().is_none());
+}
+
+#[test]
+pub fn mutation_detaches_weak() {
+    let mut s = StrongRcString::from("foo");
+    let w = s.downgrade();
+    s.push_str("bar");
+    assert_eq!(s, "foobar");
+    assert!(w.upgrade().is_none());
+}
+
+#[test]
+pub fn debug() {
+    let s = StrongRcString::from("foo");
+    let w = s.downgrade();
+    assert_eq!(format!("{:?}", w), "RcWeakString(\"foo\")");
+    mem::drop(s);
+    assert_eq!(format!("{:?}", w), "RcWeakString(<dropped>)");
+}
+
+#[test]
+pub fn atomic_downgrade_upgrade() {
+    let s = StrongArcString::from("foo");
+    let w = s.downgrade();
+    assert_eq!(w.upgrade().unwrap(), "foo");
+    assert_eq!(format!("{:?}", w), "ArcWeakString(\"foo\")");
+    mem::drop(s);
+    assert!(w.upgrade().is_none());
+}
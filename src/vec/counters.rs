@@ -1,6 +1,8 @@
-use crate::base::{Counter, VecType};
+pub use crate::base::{Counter, VecType};
 use core::cell::Cell;
 use core::convert::Infallible;
+use core::hint;
+use core::sync::atomic::{fence, AtomicUsize, Ordering};
 
 #[derive(Clone, Default)]
 pub struct StrongWeakCounter {
@@ -8,7 +10,14 @@ pub struct StrongWeakCounter {
     total: Cell<usize>,
 }
 
-impl Counter for StrongWeakCounter {}
+impl Counter for StrongWeakCounter {
+    fn strong_count(&self) -> usize {
+        self.strong.get()
+    }
+    fn weak_count(&self) -> usize {
+        self.total.get() - self.strong.get()
+    }
+}
 
 impl StrongWeakCounter {
     pub fn incr_strong(&self) {
@@ -47,7 +56,11 @@ pub struct AcyclicCounter {
     count: Cell<usize>,
 }
 
-impl Counter for AcyclicCounter {}
+impl Counter for AcyclicCounter {
+    fn strong_count(&self) -> usize {
+        self.count.get()
+    }
+}
 
 impl AcyclicCounter {
     pub fn incr(&self) {
@@ -75,8 +88,12 @@ unsafe impl VecType for StrongType {
     fn incr(counter: &StrongWeakCounter) {
         counter.incr_strong();
     }
-    fn decr(counter: &StrongWeakCounter) {
+    fn decr<F: FnOnce()>(counter: &StrongWeakCounter, drop_contents: F) -> bool {
         counter.decr_strong();
+        if !counter.valid_strong() {
+            drop_contents();
+        }
+        !counter.valid_weak()
     }
     fn can_take(counter: &StrongWeakCounter) -> bool {
         counter.unique_strong()
@@ -87,14 +104,13 @@ unsafe impl VecType for StrongType {
     fn can_get_mut(counter: &StrongWeakCounter) -> bool {
         counter.unique_weak()
     }
-    fn should_drop_entire_vector(counter: &StrongWeakCounter) -> bool {
-        !counter.valid_weak()
-    }
-    fn should_drop_contents(counter: &StrongWeakCounter) -> bool {
-        !counter.valid_strong()
-    }
-    fn can_create(counter: &StrongWeakCounter) -> bool {
-        counter.valid_strong()
+    fn try_incr(counter: &StrongWeakCounter) -> bool {
+        if counter.valid_strong() {
+            counter.incr_strong();
+            true
+        } else {
+            false
+        }
     }
 }
 
@@ -107,8 +123,9 @@ unsafe impl VecType for WeakType {
     fn incr(counter: &StrongWeakCounter) {
         counter.incr_weak();
     }
-    fn decr(counter: &StrongWeakCounter) {
+    fn decr<F: FnOnce()>(counter: &StrongWeakCounter, _: F) -> bool {
         counter.decr_weak();
+        !counter.valid_weak()
     }
     fn can_take(_: &StrongWeakCounter) -> bool {
         false
@@ -119,13 +136,8 @@ unsafe impl VecType for WeakType {
     fn can_get_mut(_: &StrongWeakCounter) -> bool {
         false
     }
-    fn should_drop_entire_vector(counter: &StrongWeakCounter) -> bool {
-        !counter.valid_weak()
-    }
-    fn should_drop_contents(_: &StrongWeakCounter) -> bool {
-        false
-    }
-    fn can_create(_: &StrongWeakCounter) -> bool {
+    fn try_incr(counter: &StrongWeakCounter) -> bool {
+        counter.incr_weak();
         true
     }
 }
@@ -139,8 +151,13 @@ unsafe impl VecType for AcycType {
     fn incr(counter: &AcyclicCounter) {
         counter.incr();
     }
-    fn decr(counter: &AcyclicCounter) {
+    fn decr<F: FnOnce()>(counter: &AcyclicCounter, drop_contents: F) -> bool {
         counter.decr();
+        if counter.valid() {
+            return false;
+        }
+        drop_contents();
+        true
     }
     fn can_take(counter: &AcyclicCounter) -> bool {
         counter.unique()
@@ -151,13 +168,517 @@ unsafe impl VecType for AcycType {
     fn can_get_mut(counter: &AcyclicCounter) -> bool {
         counter.unique()
     }
-    fn should_drop_entire_vector(counter: &AcyclicCounter) -> bool {
-        !counter.valid()
+    fn try_incr(counter: &AcyclicCounter) -> bool {
+        counter.incr();
+        true
+    }
+}
+
+/// Thread-safe counterpart of `StrongWeakCounter`.
+/// As in `alloc::sync::Arc`, all strong references collectively hold a single weak reference,
+/// so the vector is only deallocated after its contents have been dropped.
+pub struct AtomicStrongWeakCounter {
+    strong: AtomicUsize,
+    weak: AtomicUsize,
+}
+
+impl Counter for AtomicStrongWeakCounter {
+    fn strong_count(&self) -> usize {
+        self.strong.load(Ordering::Acquire)
+    }
+    fn weak_count(&self) -> usize {
+        let weak = self.weak.load(Ordering::Acquire);
+        if weak == Self::LOCKED {
+            // only a unique strong reference can lock the weak count
+            return 0;
+        }
+        if self.strong.load(Ordering::Acquire) > 0 {
+            // don't count the weak reference held collectively by the strong references
+            weak - 1
+        } else {
+            weak
+        }
+    }
+}
+
+impl Default for AtomicStrongWeakCounter {
+    fn default() -> Self {
+        Self {
+            strong: AtomicUsize::new(0),
+            weak: AtomicUsize::new(1),
+        }
+    }
+}
+
+impl Clone for AtomicStrongWeakCounter {
+    fn clone(&self) -> Self {
+        Self {
+            strong: AtomicUsize::new(self.strong.load(Ordering::Relaxed)),
+            weak: AtomicUsize::new(self.weak.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl AtomicStrongWeakCounter {
+    /// Sentinel stored in `weak` while `unique` is checking the strong count.
+    const LOCKED: usize = usize::MAX;
+
+    pub fn incr_strong(&self) {
+        self.strong.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Increments the strong count unless it has already reached zero.
+    pub fn try_incr_strong(&self) -> bool {
+        let mut prev = self.strong.load(Ordering::Relaxed);
+        loop {
+            if prev == 0 {
+                return false;
+            }
+            match self.strong.compare_exchange_weak(
+                prev,
+                prev + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(x) => prev = x,
+            }
+        }
+    }
+    pub fn incr_weak(&self) {
+        let mut prev = self.weak.load(Ordering::Relaxed);
+        loop {
+            // wait for a concurrent `unique` check to finish
+            if prev == Self::LOCKED {
+                hint::spin_loop();
+                prev = self.weak.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.weak.compare_exchange_weak(
+                prev,
+                prev + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(x) => prev = x,
+            }
+        }
+    }
+    /// Returns `true` if this was the last strong reference.
+    pub fn decr_strong(&self) -> bool {
+        Self::decr_last(&self.strong)
+    }
+    /// Returns `true` if this was the last weak reference.
+    pub fn decr_weak(&self) -> bool {
+        Self::decr_last(&self.weak)
+    }
+    fn decr_last(count: &AtomicUsize) -> bool {
+        if count.fetch_sub(1, Ordering::Release) != 1 {
+            return false;
+        }
+        // synchronize with every other handle's release before tearing anything down
+        fence(Ordering::Acquire);
+        true
+    }
+    /// Releases the last strong reference, failing if there are others. Weak references don't
+    /// prevent this, but can no longer be upgraded once it succeeds.
+    pub fn take_last_strong(&self) -> bool {
+        self.strong
+            .compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+    pub fn valid_strong(&self) -> bool {
+        self.strong.load(Ordering::Acquire) > 0
+    }
+    /// Returns `true` if the caller holds the only strong reference and there are no weak
+    /// references.
+    pub fn unique(&self) -> bool {
+        // lock the weak count so no weak reference can be created or upgraded meanwhile
+        if self
+            .weak
+            .compare_exchange(1, Self::LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+        let unique = self.strong.load(Ordering::Acquire) == 1;
+        self.weak.store(1, Ordering::Release);
+        unique
+    }
+}
+
+/// Thread-safe counterpart of `AcyclicCounter`.
+#[derive(Default)]
+pub struct AtomicAcyclicCounter {
+    count: AtomicUsize,
+}
+
+impl Counter for AtomicAcyclicCounter {
+    fn strong_count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+}
+
+impl Clone for AtomicAcyclicCounter {
+    fn clone(&self) -> Self {
+        Self {
+            count: AtomicUsize::new(self.count.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl AtomicAcyclicCounter {
+    pub fn incr(&self) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Returns `true` if this was the last reference.
+    pub fn decr(&self) -> bool {
+        if self.count.fetch_sub(1, Ordering::Release) != 1 {
+            return false;
+        }
+        fence(Ordering::Acquire);
+        true
+    }
+    pub fn valid(&self) -> bool {
+        self.count.load(Ordering::Acquire) > 0
+    }
+    pub fn unique(&self) -> bool {
+        self.count.load(Ordering::Acquire) <= 1
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct AtomicStrongType(Infallible);
+
+unsafe impl VecType for AtomicStrongType {
+    type Counter = AtomicStrongWeakCounter;
+
+    fn incr(counter: &AtomicStrongWeakCounter) {
+        counter.incr_strong();
+    }
+    fn decr<F: FnOnce()>(counter: &AtomicStrongWeakCounter, drop_contents: F) -> bool {
+        if !counter.decr_strong() {
+            return false;
+        }
+        drop_contents();
+        // release the weak reference held collectively by the strong references
+        counter.decr_weak()
+    }
+    fn can_take(counter: &AtomicStrongWeakCounter) -> bool {
+        counter.strong_count() == 1
+    }
+    fn try_take<F: FnOnce()>(counter: &AtomicStrongWeakCounter, take_contents: F) -> Option<bool> {
+        if !counter.take_last_strong() {
+            return None;
+        }
+        take_contents();
+        Some(counter.decr_weak())
+    }
+    fn can_get_ref(counter: &AtomicStrongWeakCounter) -> bool {
+        counter.valid_strong()
+    }
+    fn can_get_mut(counter: &AtomicStrongWeakCounter) -> bool {
+        counter.unique()
+    }
+    fn try_incr(counter: &AtomicStrongWeakCounter) -> bool {
+        counter.try_incr_strong()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct AtomicWeakType(Infallible);
+
+unsafe impl VecType for AtomicWeakType {
+    type Counter = AtomicStrongWeakCounter;
+
+    fn incr(counter: &AtomicStrongWeakCounter) {
+        counter.incr_weak();
+    }
+    fn decr<F: FnOnce()>(counter: &AtomicStrongWeakCounter, _: F) -> bool {
+        counter.decr_weak()
+    }
+    fn can_take(_: &AtomicStrongWeakCounter) -> bool {
+        false
+    }
+    fn can_get_ref(_: &AtomicStrongWeakCounter) -> bool {
+        false
+    }
+    fn can_get_mut(_: &AtomicStrongWeakCounter) -> bool {
+        false
+    }
+    fn try_incr(counter: &AtomicStrongWeakCounter) -> bool {
+        counter.incr_weak();
+        true
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct AtomicAcycType(Infallible);
+
+unsafe impl VecType for AtomicAcycType {
+    type Counter = AtomicAcyclicCounter;
+
+    fn incr(counter: &AtomicAcyclicCounter) {
+        counter.incr();
+    }
+    fn decr<F: FnOnce()>(counter: &AtomicAcyclicCounter, drop_contents: F) -> bool {
+        if !counter.decr() {
+            return false;
+        }
+        drop_contents();
+        true
+    }
+    fn can_take(counter: &AtomicAcyclicCounter) -> bool {
+        counter.unique()
+    }
+    fn can_get_ref(counter: &AtomicAcyclicCounter) -> bool {
+        counter.valid()
+    }
+    fn can_get_mut(counter: &AtomicAcyclicCounter) -> bool {
+        counter.unique()
+    }
+    fn try_incr(counter: &AtomicAcyclicCounter) -> bool {
+        counter.incr();
+        true
+    }
+}
+
+/// The colour of a vector during cycle collection, as named by Bacon and Rajan.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) enum Color {
+    /// In use, or not yet looked at.
+    Black = 0,
+    /// Being checked for references from outside its cycle.
+    Gray = 1,
+    /// Only referenced from within a garbage cycle.
+    White = 2,
+    /// Possibly the root of a garbage cycle.
+    Purple = 3,
+}
+
+/// Counter for `CycType`, which also stores the state the cycle collector keeps per vector.
+#[derive(Clone, Default)]
+pub struct CycleCounter {
+    count: Cell<usize>,
+    flags: Cell<u8>,
+}
+
+impl Counter for CycleCounter {
+    fn strong_count(&self) -> usize {
+        self.count.get()
+    }
+}
+
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+impl CycleCounter {
+    const COLOR: u8 = 0b11;
+    /// Set while the vector is in the collector's list of possible roots.
+    const BUFFERED: u8 = 0b100;
+    /// Set once the collector has decided to free the vector, so dropping the handles inside
+    /// other garbage vectors must not free it a second time.
+    const DEAD: u8 = 0b1000;
+
+    pub fn incr(&self) {
+        assert!(
+            !self.is_dead(),
+            "a vector was revived while its cycle was being collected"
+        );
+        self.incr_unchecked();
+    }
+    pub(crate) fn incr_unchecked(&self) {
+        let prev = self.count.get();
+        self.count.set(prev + 1);
+    }
+    pub(crate) fn decr_unchecked(&self) {
+        let prev = self.count.get();
+        self.count.set(prev - 1);
+    }
+    pub fn valid(&self) -> bool {
+        self.count.get() > 0 && !self.is_dead()
+    }
+    pub fn unique(&self) -> bool {
+        self.count.get() <= 1 && !self.is_dead()
+    }
+    pub(crate) fn color(&self) -> Color {
+        match self.flags.get() & Self::COLOR {
+            0 => Color::Black,
+            1 => Color::Gray,
+            2 => Color::White,
+            _ => Color::Purple,
+        }
+    }
+    pub(crate) fn set_color(&self, color: Color) {
+        self.flags
+            .set(self.flags.get() & !Self::COLOR | color as u8);
+    }
+    pub(crate) fn is_buffered(&self) -> bool {
+        self.flags.get() & Self::BUFFERED != 0
+    }
+    pub(crate) fn set_buffered(&self, buffered: bool) {
+        self.set_flag(Self::BUFFERED, buffered);
+    }
+    pub(crate) fn is_dead(&self) -> bool {
+        self.flags.get() & Self::DEAD != 0
+    }
+    pub(crate) fn set_dead(&self) {
+        self.set_flag(Self::DEAD, true);
     }
-    fn should_drop_contents(counter: &AcyclicCounter) -> bool {
-        !counter.valid()
+    fn set_flag(&self, flag: u8, value: bool) {
+        let flags = self.flags.get();
+        self.flags
+            .set(if value { flags | flag } else { flags & !flag });
     }
-    fn can_create(_: &AcyclicCounter) -> bool {
+}
+
+/// A strong reference whose cycles can be reclaimed by `cycle::collect_cycles`.
+/// Handles must be held as `cycle::CcVec` for dropped cycles to be found.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct CycType(Infallible);
+
+unsafe impl VecType for CycType {
+    type Counter = CycleCounter;
+
+    fn incr(counter: &CycleCounter) {
+        counter.incr();
+        counter.set_color(Color::Black);
+    }
+    fn decr<F: FnOnce()>(counter: &CycleCounter, drop_contents: F) -> bool {
+        counter.decr_unchecked();
+        if counter.strong_count() > 0 || counter.is_dead() {
+            // the collector frees dead vectors itself
+            return false;
+        }
+        counter.set_color(Color::Black);
+        drop_contents();
+        // a vector in the list of possible roots is freed when the collector next runs
+        !counter.is_buffered()
+    }
+    fn can_take(counter: &CycleCounter) -> bool {
+        Self::can_get_mut(counter)
+    }
+    fn can_get_ref(counter: &CycleCounter) -> bool {
+        counter.valid()
+    }
+    fn can_get_mut(counter: &CycleCounter) -> bool {
+        // the collector remembers possible roots by address, so they must stay where they are
+        // until it has run; mutating one copies it instead
+        counter.unique() && !counter.is_buffered()
+    }
+    fn try_incr(counter: &CycleCounter) -> bool {
+        if counter.is_dead() {
+            return false;
+        }
+        Self::incr(counter);
         true
     }
 }
+
+/// Hands out a distinct lineage to every versioned vector. Zero is left for empty placeholders.
+static NEXT_LINEAGE: AtomicUsize = AtomicUsize::new(1);
+
+/// Identifies the state of the contents of a `VersionedType` vector. Two versions are only
+/// equal if the contents can't have changed between them.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Version {
+    /// Distinguishes the buffer, so a copy made by copy-on-write never matches the original.
+    lineage: usize,
+    count: u64,
+}
+
+impl Version {
+    /// The version of every empty vector that hasn't allocated yet.
+    pub(crate) const EMPTY: Self = Self {
+        lineage: 0,
+        count: 0,
+    };
+}
+
+/// Counter for `VersionedType`, which also counts how many times the vector has been handed out
+/// for mutation.
+#[derive(Clone)]
+pub struct VersionedCounter {
+    count: Cell<usize>,
+    lineage: usize,
+    version: Cell<u64>,
+}
+
+impl Counter for VersionedCounter {
+    fn strong_count(&self) -> usize {
+        self.count.get()
+    }
+}
+
+impl Default for VersionedCounter {
+    fn default() -> Self {
+        Self {
+            count: Cell::new(0),
+            lineage: NEXT_LINEAGE.fetch_add(1, Ordering::Relaxed),
+            version: Cell::new(0),
+        }
+    }
+}
+
+impl VersionedCounter {
+    pub fn incr(&self) {
+        let prev = self.count.get();
+        self.count.set(prev + 1);
+    }
+    pub fn decr(&self) {
+        let prev = self.count.get();
+        self.count.set(prev - 1);
+    }
+    pub fn valid(&self) -> bool {
+        self.count.get() > 0
+    }
+    pub fn unique(&self) -> bool {
+        self.count.get() <= 1
+    }
+    pub fn version(&self) -> Version {
+        Version {
+            lineage: self.lineage,
+            count: self.version.get(),
+        }
+    }
+    pub fn bump_version(&self) {
+        let prev = self.version.get();
+        self.version.set(prev + 1);
+    }
+}
+
+/// Like `AcycType`, but the vector records a new `Version` every time a handle is given mutable
+/// access, so readers can cheaply tell whether it may have changed.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct VersionedType(Infallible);
+
+unsafe impl VecType for VersionedType {
+    type Counter = VersionedCounter;
+
+    fn incr(counter: &VersionedCounter) {
+        counter.incr();
+    }
+    fn decr<F: FnOnce()>(counter: &VersionedCounter, drop_contents: F) -> bool {
+        counter.decr();
+        if counter.valid() {
+            return false;
+        }
+        drop_contents();
+        true
+    }
+    fn can_take(counter: &VersionedCounter) -> bool {
+        counter.unique()
+    }
+    fn can_get_ref(counter: &VersionedCounter) -> bool {
+        counter.valid()
+    }
+    fn can_get_mut(counter: &VersionedCounter) -> bool {
+        counter.unique()
+    }
+    fn try_incr(counter: &VersionedCounter) -> bool {
+        counter.incr();
+        true
+    }
+    fn on_mut(counter: &VersionedCounter) {
+        counter.bump_version();
+    }
+}
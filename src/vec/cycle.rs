@@ -0,0 +1,416 @@
+//! Synchronous cycle collection for vectors that hold handles to each other, using the trial
+//! deletion algorithm from Bacon and Rajan's "Concurrent Cycle Collection in Reference Counted
+//! Systems".
+//!
+//! Dropping a `CcVec` handle that isn't the last one remembers the vector as a possible root of
+//! a garbage cycle. `collect_cycles` then subtracts the references each of those vectors and
+//! everything reachable from them hold to each other; whatever is left without references from
+//! outside is garbage and gets freed. The possible roots are kept per thread.
+
+use super::counters::{Color, CycType, CycleCounter};
+use super::generic_vec::GenericVec;
+use crate::base::{BaseRcVec, Counter};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use allocator_api2::alloc::{Allocator, Global};
+use core::cell::RefCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+/// Types that can report the `CcVec` handles they own to the cycle collector.
+///
+/// # Safety
+/// `trace` must pass every `CcVec` handle owned by `self` to the tracer exactly once, and must
+/// report the same handles every time it is called while nothing is mutated. Handles that are
+/// only shared with `self`, such as ones behind an `Rc`, must not be traced. Tracing too few
+/// handles only leaks memory, but tracing a handle too often lets the collector free vectors that
+/// are still in use.
+pub unsafe trait Trace {
+    fn trace(&self, tracer: &mut Tracer<'_>);
+}
+
+/// Receives the handles reported by `Trace::trace`.
+pub struct Tracer<'a> {
+    visit: &'a mut dyn FnMut(Node),
+}
+
+impl<'a> Tracer<'a> {
+    fn visit(&mut self, node: Node) {
+        (self.visit)(node)
+    }
+}
+
+/// A type-erased vector, as seen by the collector.
+#[derive(Copy, Clone)]
+struct Node {
+    ptr: NonNull<u8>,
+    ops: &'static NodeOps,
+}
+
+struct NodeOps {
+    counter: unsafe fn(NonNull<u8>) -> NonNull<CycleCounter>,
+    trace: unsafe fn(NonNull<u8>, &mut Tracer<'_>),
+    drop_contents: unsafe fn(NonNull<u8>),
+    dealloc: unsafe fn(NonNull<u8>),
+}
+
+struct Ops<T, A>(PhantomData<(T, A)>);
+
+impl<T: Trace, A: Allocator + Clone> Ops<T, A> {
+    unsafe fn counter(ptr: NonNull<u8>) -> NonNull<CycleCounter> {
+        let base = BaseRcVec::<CycType, T, A>::from_raw(ptr);
+        // nodes are only created for vectors with a header
+        NonNull::from(base.counter().unwrap())
+    }
+    unsafe fn trace(ptr: NonNull<u8>, tracer: &mut Tracer<'_>) {
+        let base = BaseRcVec::<CycType, T, A>::from_raw(ptr);
+        for item in base.contents_unchecked() {
+            item.trace(tracer);
+        }
+    }
+    unsafe fn drop_contents(ptr: NonNull<u8>) {
+        BaseRcVec::<CycType, T, A>::from_raw(ptr).drop_contents_unchecked();
+    }
+    unsafe fn dealloc(ptr: NonNull<u8>) {
+        BaseRcVec::<CycType, T, A>::from_raw(ptr).dealloc_unchecked();
+    }
+}
+
+impl Node {
+    fn of<T: Trace, A: Allocator + Clone>(base: &BaseRcVec<CycType, T, A>) -> Option<Self> {
+        base.counter()?;
+        Some(Self {
+            ptr: base.as_raw(),
+            ops: &NodeOps {
+                counter: Ops::<T, A>::counter,
+                trace: Ops::<T, A>::trace,
+                drop_contents: Ops::<T, A>::drop_contents,
+                dealloc: Ops::<T, A>::dealloc,
+            },
+        })
+    }
+
+    /// SAFETY: the vector must not have been deallocated.
+    unsafe fn counter<'a>(self) -> &'a CycleCounter {
+        &*(self.ops.counter)(self.ptr).as_ptr()
+    }
+
+    /// SAFETY: the contents must not have been dropped.
+    unsafe fn for_each_child(self, mut f: impl FnMut(Node)) {
+        (self.ops.trace)(self.ptr, &mut Tracer { visit: &mut f })
+    }
+}
+
+std::thread_local! {
+    static ROOTS: RefCell<Vec<Node>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Remembers a vector that just lost a handle as a possible root of a garbage cycle.
+fn possible_root(node: Node) {
+    let counter = unsafe { node.counter() };
+    counter.set_color(Color::Purple);
+    if counter.is_buffered() {
+        return;
+    }
+    // during thread teardown the vector is simply never collected
+    let _ = ROOTS.try_with(|roots| {
+        roots.borrow_mut().push(node);
+        counter.set_buffered(true);
+    });
+}
+
+/// Frees every vector that is only reachable from garbage cycles among the vectors that lost a
+/// `CcVec` handle on this thread since the last collection.
+///
+/// The elements of garbage vectors are dropped before any of the vectors are deallocated. Their
+/// destructors must not read other cycle-collected vectors or keep handles to them, which panics.
+pub fn collect_cycles() {
+    let roots = ROOTS.with(|roots| mem::take(&mut *roots.borrow_mut()));
+    // SAFETY: every node reached below is either buffered or referenced by a live vector, so it
+    // hasn't been deallocated, and only the last phase runs code outside the collector
+    unsafe {
+        let mut candidates = Vec::new();
+        for node in roots {
+            let counter = node.counter();
+            if counter.color() == Color::Purple {
+                mark_gray(node);
+                candidates.push(node);
+            } else {
+                counter.set_buffered(false);
+                if counter.color() == Color::Black && counter.strong_count() == 0 {
+                    // the contents were dropped when the last handle went away
+                    (node.ops.dealloc)(node.ptr);
+                }
+            }
+        }
+        for &node in &candidates {
+            scan(node);
+        }
+        let mut garbage = Vec::new();
+        for node in candidates {
+            node.counter().set_buffered(false);
+            collect_white(node, &mut garbage);
+        }
+        // put back the references between garbage vectors, so dropping the contents releases
+        // each handle exactly once
+        for &node in &garbage {
+            node.for_each_child(|child| child.counter().incr_unchecked());
+        }
+        for &node in &garbage {
+            (node.ops.drop_contents)(node.ptr);
+        }
+        for node in garbage {
+            (node.ops.dealloc)(node.ptr);
+        }
+    }
+}
+
+/// Subtracts the references held by everything reachable from `root`.
+unsafe fn mark_gray(root: Node) {
+    if root.counter().color() == Color::Gray {
+        return;
+    }
+    root.counter().set_color(Color::Gray);
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        node.for_each_child(|child| {
+            let counter = child.counter();
+            counter.decr_unchecked();
+            if counter.color() != Color::Gray {
+                counter.set_color(Color::Gray);
+                stack.push(child);
+            }
+        });
+    }
+}
+
+/// Marks gray vectors that are still referenced from outside as live, and the rest as garbage.
+unsafe fn scan(root: Node) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let counter = node.counter();
+        if counter.color() != Color::Gray {
+            continue;
+        }
+        if counter.strong_count() > 0 {
+            scan_black(node);
+        } else {
+            counter.set_color(Color::White);
+            node.for_each_child(|child| stack.push(child));
+        }
+    }
+}
+
+/// Restores the references held by everything reachable from a live vector.
+unsafe fn scan_black(root: Node) {
+    root.counter().set_color(Color::Black);
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        node.for_each_child(|child| {
+            let counter = child.counter();
+            counter.incr_unchecked();
+            if counter.color() != Color::Black {
+                counter.set_color(Color::Black);
+                stack.push(child);
+            }
+        });
+    }
+}
+
+/// Gathers the garbage reachable from `root`, leaving vectors that are still buffered for when
+/// their own turn comes.
+unsafe fn collect_white(root: Node, garbage: &mut Vec<Node>) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let counter = node.counter();
+        if counter.color() != Color::White || counter.is_buffered() {
+            continue;
+        }
+        counter.set_color(Color::Black);
+        counter.set_dead();
+        garbage.push(node);
+        node.for_each_child(|child| stack.push(child));
+    }
+}
+
+/// A handle to a shared vector whose reference cycles are freed by `collect_cycles`.
+/// Dereferences to the underlying `GenericVec`, but only handles held as `CcVec` are noticed by
+/// the collector when they are dropped.
+///
+/// A vector that lost a handle stays where it is until the next collection, so mutating it before
+/// then copies it even if only one handle is left.
+pub struct CcVec<T: Trace, A: Allocator + Clone = Global> {
+    inner: GenericVec<CycType, T, A>,
+}
+
+impl<T: Trace> CcVec<T> {
+    pub const fn new() -> Self {
+        Self {
+            inner: GenericVec::new(),
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        GenericVec::with_capacity(cap).into()
+    }
+}
+
+impl<T: Trace, A: Allocator + Clone> CcVec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        GenericVec::new_in(alloc).into()
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        GenericVec::with_capacity_in(cap, alloc).into()
+    }
+}
+
+impl<T: Trace, A: Allocator + Clone> Drop for CcVec<T, A> {
+    fn drop(&mut self) {
+        let node = match Node::of(&self.inner.base) {
+            Some(node) => node,
+            None => return,
+        };
+        let counter = unsafe { node.counter() };
+        if counter.strong_count() > 1 && !counter.is_dead() {
+            possible_root(node);
+        }
+    }
+}
+
+impl<T: Trace, A: Allocator + Clone> Deref for CcVec<T, A> {
+    type Target = GenericVec<CycType, T, A>;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: Trace, A: Allocator + Clone> DerefMut for CcVec<T, A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<T: Trace, A: Allocator + Clone> Clone for CcVec<T, A> {
+    fn clone(&self) -> Self {
+        self.inner.clone().into()
+    }
+}
+
+impl<T: Trace> Default for CcVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trace + fmt::Debug, A: Allocator + Clone> fmt::Debug for CcVec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl<T: Trace, A: Allocator + Clone> From<GenericVec<CycType, T, A>> for CcVec<T, A> {
+    fn from(inner: GenericVec<CycType, T, A>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Trace> From<Vec<T>> for CcVec<T> {
+    fn from(src: Vec<T>) -> Self {
+        GenericVec::from(src).into()
+    }
+}
+
+impl<T: Trace> core::iter::FromIterator<T> for CcVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        GenericVec::from_iter(iter).into()
+    }
+}
+
+unsafe impl<T: Trace, A: Allocator + Clone> Trace for CcVec<T, A> {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        if let Some(node) = Node::of(&self.inner.base) {
+            tracer.visit(node);
+        }
+    }
+}
+
+macro_rules! trace_leaf {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            unsafe impl Trace for $ty {
+                fn trace(&self, _: &mut Tracer<'_>) {}
+            }
+        )*
+    };
+}
+
+trace_leaf!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    String,
+    &'static str,
+);
+
+unsafe impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        if let Some(x) = self {
+            x.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace + ?Sized> Trace for Box<T> {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        (**self).trace(tracer);
+    }
+}
+
+unsafe impl<T: Trace> Trace for [T] {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        for x in self {
+            x.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace, const N: usize> Trace for [T; N] {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        self[..].trace(tracer);
+    }
+}
+
+unsafe impl<T: Trace> Trace for Vec<T> {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        self[..].trace(tracer);
+    }
+}
+
+/// Handles behind a mutable borrow that is held during collection are skipped, which keeps
+/// everything they reference alive until the next collection.
+unsafe impl<T: Trace + ?Sized> Trace for RefCell<T> {
+    fn trace(&self, tracer: &mut Tracer<'_>) {
+        if let Ok(x) = self.try_borrow() {
+            x.trace(tracer);
+        }
+    }
+}
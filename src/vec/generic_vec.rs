@@ -1,31 +1,214 @@
+use crate::base::header_vec::{HeaderVec, IntoValues};
 use crate::base::vec_ref::{VecMut, VecRef};
 use crate::base::{BaseRcVec, VecType};
+use crate::error::{NotUnique, TryMutError, TryReserveError};
+use crate::vec::counters::{Version, VersionedCounter, VersionedType};
+use crate::vec::slice::resolve_range;
+use crate::vec::unique::UniqueVec;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec::{self, Vec};
+use allocator_api2::alloc::{Allocator, Global};
+use core::borrow::Borrow;
 use core::cmp::Ordering;
 use core::fmt;
-use core::ops::{Deref, DerefMut};
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, DerefMut, RangeBounds};
 use core::ops::{Index, IndexMut};
 use core::slice::SliceIndex;
-use header_slice::HeaderVec;
 
-pub struct GenericVec<V: VecType, T> {
-    pub(super) base: BaseRcVec<V, T>,
+/// A shared vector whose buffer is allocated by `A`. Every handle to it stores only a pointer;
+/// the allocator lives in the header alongside the counter.
+#[repr(transparent)]
+pub struct GenericVec<V: VecType, T, A: Allocator + Clone = Global> {
+    pub(super) base: BaseRcVec<V, T, A>,
 }
 
 impl<V: VecType, T> GenericVec<V, T> {
-    pub fn new() -> Self {
+    /// Doesn't allocate until the vector is first mutated, and can be used in constant expressions.
+    pub const fn new() -> Self {
         Self {
-            base: BaseRcVec::from_vec(HeaderVec::default()),
+            base: BaseRcVec::new(),
+        }
+    }
+
+    /// Wraps `src` without copying it or allocating. The elements are copied on the first
+    /// mutation. `src` points to the slice, as in `static DATA: &[i32] = &[1, 2, 3]` passed as
+    /// `&DATA`, so that the handle can stay a single pointer.
+    ///
+    /// Handles to static data aren't counted: they report a strong count of 1, but never own
+    /// the contents, so `try_unwrap` fails and `into_inner` returns `None`. Weak handles to them
+    /// always upgrade, since the contents are never freed.
+    pub const fn from_static(src: &'static &'static [T]) -> Self {
+        Self {
+            base: BaseRcVec::from_static(src),
         }
     }
 
     pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Like `with_capacity`, but returns an error instead of panicking if allocation fails.
+    pub fn try_with_capacity(cap: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+
+    /// Copies `len` elements starting at `ptr` into a new vector.
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads of `len` elements, and the originals must not be used or
+    /// dropped afterwards.
+    pub unsafe fn copy_from_ptr_unsafe(ptr: *mut T, len: usize) -> Self {
+        let v = HeaderVec::copy_from_ptr_unsafe_in(Default::default(), ptr, len, Global);
         Self {
-            base: BaseRcVec::from_vec(HeaderVec::with_capacity(Default::default(), cap)),
+            base: BaseRcVec::from_vec(v),
         }
     }
 }
 
-impl<V: VecType, T: Clone> GenericVec<V, T> {
+impl<V: VecType, T, A: Allocator + Clone> GenericVec<V, T, A> {
+    /// Unlike `new`, this allocates a header to hold `alloc`, but no room for elements.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_capacity_in(0, alloc)
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Self {
+            base: BaseRcVec::from_vec(HeaderVec::with_capacity_in(Default::default(), cap, alloc)),
+        }
+    }
+
+    /// Like `with_capacity_in`, but returns an error instead of panicking if allocation fails.
+    pub fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let v = HeaderVec::try_with_capacity_in(Default::default(), cap, alloc)?;
+        Ok(Self {
+            base: BaseRcVec::from_vec(v),
+        })
+    }
+
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(it: I, alloc: A) -> Self {
+        Self {
+            base: BaseRcVec::from_vec(HeaderVec::from_iter_in(Default::default(), it, alloc)),
+        }
+    }
+
+    /// The allocator that the vector and any copies of it made on mutation are allocated in.
+    pub fn allocator(&self) -> &A {
+        self.base.allocator()
+    }
+
+    /// The number of handles that can access the contents, including this one.
+    pub fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+
+    /// The number of weak handles to the vector.
+    pub fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+
+    /// Whether this is the only handle to the vector, i.e. whether mutating it won't copy.
+    pub fn is_unique(&self) -> bool {
+        self.base.can_get_mut()
+    }
+
+    /// Whether both handles point to the same vector. This takes O(1), so use it rather than
+    /// `==` to find handles that share a vector.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.base.as_ptr()
+    }
+
+    /// Identifies the buffer for `SharedGraph`. Empty vectors that haven't allocated yet all
+    /// share one placeholder, so they have no identity of their own.
+    #[cfg(all(feature = "serde", feature = "std"))]
+    pub(crate) fn shared_id(&self) -> Option<*const u8> {
+        self.base.counter().map(|_| self.as_ptr() as *const u8)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.base.try_vec_ref().unwrap().capacity()
+    }
+
+    /// Returns a mutable slice of the contents if this is the only handle to the vector.
+    pub fn get_mut(&mut self) -> Result<&mut [T], NotUnique> {
+        Ok(self.unique_mut()?)
+    }
+
+    /// Gives access to the full mutating API of `UniqueVec` if this is the only handle to the
+    /// vector. Unlike the copy-on-write methods, this doesn't require `T: Clone`.
+    pub fn unique_mut(&mut self) -> Result<&mut UniqueVec<V, T, A>, NotUnique> {
+        if !self.base.can_get_mut() {
+            return Err(NotUnique);
+        }
+        // SAFETY: both types are transparent wrappers around `BaseRcVec<V, T, A>`, and the handle
+        // can't be shared while it is mutably borrowed.
+        Ok(unsafe { &mut *(self as *mut Self as *mut UniqueVec<V, T, A>) })
+    }
+
+    /// Returns the contents if this is the last handle that can access them, even if weak
+    /// handles remain. Otherwise, just drops this handle.
+    /// If this is called on every handle to a vector, exactly one of them returns `Some`.
+    pub fn into_inner(self) -> Option<Vec<T>> {
+        self.base.into_inner()
+    }
+
+    /// Returns the contents if this is the only handle that can access them, like
+    /// `Rc::try_unwrap`. Weak handles don't prevent this, and can't be upgraded afterwards.
+    /// The elements are moved, not cloned, but they are moved into a new allocation.
+    pub fn try_unwrap(self) -> Result<Vec<T>, Self> {
+        match self.base.try_into_inner() {
+            Ok(v) => Ok(v),
+            Err(base) => Err(Self { base }),
+        }
+    }
+
+    /// Converts this handle into a `UniqueVec` if it is the only handle to the vector.
+    pub fn try_into_unique(self) -> Result<UniqueVec<V, T, A>, Self> {
+        if self.base.can_get_mut() {
+            Ok(UniqueVec { base: self.base })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> GenericVec<VersionedType, T, A> {
+    /// Returns the current version of the contents. It changes whenever any handle is given
+    /// mutable access, and handles sharing the buffer see the same version.
+    pub fn version(&self) -> Version {
+        self.base
+            .counter()
+            .map_or(Version::EMPTY, VersionedCounter::version)
+    }
+
+    /// Whether the contents may have changed since `version` was returned by `version`.
+    /// A copy made by copy-on-write always counts as changed.
+    pub fn changed_since(&self, version: Version) -> bool {
+        self.version() != version
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> GenericVec<V, T, A> {
+    /// Returns the full mutating API of `UniqueVec`, copying the vector first if it is shared.
+    pub fn make_mut(&mut self) -> &mut UniqueVec<V, T, A> {
+        assert!(self.base.try_make_unique());
+        match self.unique_mut() {
+            Ok(v) => v,
+            Err(_) => unreachable!("vector was just made unique"),
+        }
+    }
+
+    /// Returns the contents, cloning them if other handles can still access them.
+    pub fn unwrap_or_clone(self) -> Vec<T> {
+        self.try_unwrap().unwrap_or_else(|this| this.to_vec())
+    }
+
     pub fn push(&mut self, val: T) {
         self.base.try_make_vec_mut().unwrap().push(val);
     }
@@ -46,53 +229,283 @@ impl<V: VecType, T: Clone> GenericVec<V, T> {
         self.base.try_make_vec_mut().unwrap().remove(index)
     }
 
-    pub unsafe fn copy_from_ptr_unsafe(ptr: *mut T, len: usize) -> Self {
-        let v = HeaderVec::copy_from_ptr_unsafe(Default::default(), ptr, len);
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        self.base.try_make_vec_mut().unwrap().swap_remove(index)
+    }
+
+    /// Like `make_mut`, but if the vector has to be copied, the copy has room for `additional`
+    /// more elements.
+    fn make_mut_with_room(&mut self, additional: usize) -> &mut UniqueVec<V, T, A> {
+        if let Err(e) = self.base.checked_make_vec_mut(additional) {
+            panic!("{}", e);
+        }
+        self.make_mut()
+    }
+
+    /// Builds a vector from `it` to replace a copy of this one, in the same allocator and with
+    /// the same counter state a copy would get.
+    fn copy_from_iter<I: IntoIterator<Item = T>>(&self, it: I) -> Self {
+        let counter = self.base.counter_for_copy();
+        let v = HeaderVec::from_iter_in(counter, it, self.allocator().clone());
         Self {
             base: BaseRcVec::from_vec(v),
         }
     }
+
+    /// Shortens the vector to `len` elements. Does nothing if it is already shorter.
+    /// If the vector is shared, only the kept elements are copied.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+        if self.is_unique() {
+            self.make_mut().truncate(len);
+        } else {
+            *self = self.copy_from_iter(self[..len].iter().cloned());
+        }
+    }
+
+    pub fn resize(&mut self, new_len: usize, val: T) {
+        let additional = new_len.saturating_sub(self.len());
+        self.make_mut_with_room(additional).resize(new_len, val);
+    }
+
+    pub fn resize_with(&mut self, new_len: usize, f: impl FnMut() -> T) {
+        let additional = new_len.saturating_sub(self.len());
+        self.make_mut_with_room(additional).resize_with(new_len, f);
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    /// If the vector is shared, only the kept elements are copied.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        if self.is_unique() {
+            self.make_mut().retain(f);
+        } else {
+            let kept = self.iter().filter(|x| f(x)).cloned();
+            *self = self.copy_from_iter(kept);
+        }
+    }
+
+    /// Like `retain`, but `f` may mutate the elements it visits.
+    pub fn retain_mut(&mut self, f: impl FnMut(&mut T) -> bool) {
+        self.make_mut().retain_mut(f);
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns `true`, keeping the first of
+    /// each run.
+    pub fn dedup_by(&mut self, same_bucket: impl FnMut(&mut T, &mut T) -> bool) {
+        self.make_mut().dedup_by(same_bucket);
+    }
+
+    /// Removes consecutive elements that map to the same key, keeping the first of each run.
+    pub fn dedup_by_key<K: PartialEq>(&mut self, key: impl FnMut(&mut T) -> K) {
+        self.make_mut().dedup_by_key(key);
+    }
+
+    /// Removes the elements in `range` and returns them in order.
+    /// Unlike `Vec::drain`, the elements are removed immediately rather than as the iterator is
+    /// consumed.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> vec::IntoIter<T> {
+        self.make_mut().drain(range)
+    }
+
+    /// Replaces the elements in `range` with `replace_with`, returning the removed elements.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> vec::IntoIter<T>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        self.make_mut().splice(range, replace_with)
+    }
+
+    /// Moves the elements from `at` onwards into a new vector.
+    /// If the vector is shared, both halves are copied from it instead.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if self.is_unique() {
+            return self.make_mut().split_off(at).freeze();
+        }
+        let tail = self.copy_from_iter(self[at..].iter().cloned());
+        *self = self.copy_from_iter(self[..at].iter().cloned());
+        tail
+    }
+
+    /// Moves every element of `other` onto the end of this vector, leaving `other` empty.
+    /// The elements are cloned if `other` is shared.
+    pub fn append(&mut self, other: &mut Self) {
+        if let Ok(other) = other.unique_mut() {
+            let moved = other.drain(..);
+            self.make_mut_with_room(moved.len()).extend(moved);
+        } else {
+            self.make_mut_with_room(other.len())
+                .extend(other.iter().cloned());
+            other.truncate(0);
+        }
+    }
+
+    /// Clones the elements in `range` onto the end of the vector.
+    pub fn extend_from_within<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = resolve_range(range, self.len());
+        self.make_mut_with_room(end - start)
+            .extend_from_within(start..end);
+    }
+
+    /// Reserves capacity for at least `additional` more elements, copying the vector if it is
+    /// shared.
+    pub fn reserve(&mut self, additional: usize) {
+        self.make_mut_with_room(additional).reserve(additional);
+    }
+
+    /// Reserves capacity for exactly `additional` more elements, copying the vector if it is
+    /// shared.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.make_mut_with_room(additional)
+            .reserve_exact(additional);
+    }
+
+    /// Frees any unused capacity. Does nothing if the vector is shared, since copying it
+    /// wouldn't save any memory.
+    pub fn shrink_to_fit(&mut self) {
+        if let Ok(v) = self.unique_mut() {
+            v.shrink_to_fit();
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more elements, copying the vector if it is
+    /// shared.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let mut v = self
+            .base
+            .checked_make_vec_mut(additional)
+            .map_err(TryMutError::into_reserve_error)?;
+        v.try_reserve(additional)
+    }
+
+    /// Reserves capacity for exactly `additional` more elements, copying the vector if it is
+    /// shared.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let mut v = self
+            .base
+            .checked_make_vec_mut(additional)
+            .map_err(TryMutError::into_reserve_error)?;
+        v.try_reserve_exact(additional)
+    }
+
+    pub fn try_push(&mut self, val: T) -> Result<(), TryMutError> {
+        let mut v = self.base.checked_make_vec_mut(1)?;
+        v.try_reserve(1)?;
+        v.push(val);
+        Ok(())
+    }
+
+    /// Removes the last element. Never shrinks the allocation.
+    pub fn try_pop(&mut self) -> Result<Option<T>, TryMutError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let mut v = self.base.checked_make_vec_mut(0)?;
+        Ok(v.pop_in_place())
+    }
+
+    pub fn try_clear(&mut self) -> Result<(), TryMutError> {
+        if self.base.can_get_mut() {
+            self.base.checked_make_vec_mut(0)?.clear_in_place();
+        } else {
+            // no need to copy the contents just to drop them
+            let counter = self.base.counter_for_copy();
+            let v = HeaderVec::try_with_capacity_in(counter, 0, self.allocator().clone())?;
+            self.base = BaseRcVec::from_vec(v);
+        }
+        Ok(())
+    }
+
+    /// Inserts an element at `index`, shifting all elements after it to the right.
+    /// Fails with `IndexOutOfBounds` if `index > self.len()`, leaving the vector untouched.
+    pub fn try_insert(&mut self, index: usize, val: T) -> Result<(), TryMutError> {
+        let len = self.len();
+        if index > len {
+            return Err(TryMutError::IndexOutOfBounds { index, len });
+        }
+        let mut v = self.base.checked_make_vec_mut(1)?;
+        v.try_reserve(1)?;
+        v.push(val);
+        v[index..].rotate_right(1);
+        Ok(())
+    }
+
+    /// Removes the element at `index`, if it exists. Never shrinks the allocation.
+    pub fn try_remove(&mut self, index: usize) -> Result<Option<T>, TryMutError> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        let mut v = self.base.checked_make_vec_mut(0)?;
+        v[index..].rotate_left(1);
+        Ok(v.pop_in_place())
+    }
+
+    /// Fallible counterpart to `DerefMut`.
+    pub fn try_as_mut_slice(&mut self) -> Result<&mut [T], TryMutError> {
+        Ok(VecMut::get_body_mut(self.base.checked_make_vec_mut(0)?))
+    }
 }
 
 impl<V: VecType, T: Copy> GenericVec<V, T> {
     pub fn copy_from_slice(src: &[T]) -> Self {
+        Self::copy_from_slice_in(src, Global)
+    }
+}
+
+impl<V: VecType, T: Copy, A: Allocator + Clone> GenericVec<V, T, A> {
+    pub fn copy_from_slice_in(src: &[T], alloc: A) -> Self {
         Self {
-            base: BaseRcVec::from_vec(HeaderVec::copy_from_slice(Default::default(), src)),
+            base: BaseRcVec::from_vec(HeaderVec::copy_from_slice_in(
+                Default::default(),
+                src,
+                alloc,
+            )),
         }
     }
 
     pub fn extend_from_slice(&mut self, src: &[T]) {
         self.base.try_make_vec_mut().unwrap().extend_from_slice(src);
     }
+
+    pub fn try_extend_from_slice(&mut self, src: &[T]) -> Result<(), TryMutError> {
+        let mut v = self.base.checked_make_vec_mut(src.len())?;
+        v.try_reserve(src.len())?;
+        v.extend_from_slice(src);
+        Ok(())
+    }
 }
 
-impl<V: VecType, T> Deref for GenericVec<V, T> {
+impl<V: VecType, T, A: Allocator + Clone> Deref for GenericVec<V, T, A> {
     type Target = [T];
     fn deref(&self) -> &[T] {
         VecRef::get_body(self.base.try_vec_ref().unwrap())
     }
 }
 
-impl<V: VecType, T: Clone> DerefMut for GenericVec<V, T> {
+impl<V: VecType, T: Clone, A: Allocator + Clone> DerefMut for GenericVec<V, T, A> {
     fn deref_mut(&mut self) -> &mut [T] {
         VecMut::get_body_mut(self.base.try_make_vec_mut().unwrap())
     }
 }
 
-impl<V: VecType, T, S: SliceIndex<[T]>> Index<S> for GenericVec<V, T> {
+impl<V: VecType, T, S: SliceIndex<[T]>, A: Allocator + Clone> Index<S> for GenericVec<V, T, A> {
     type Output = S::Output;
     fn index(&self, i: S) -> &Self::Output {
         VecRef::get_body(self.base.try_vec_ref().unwrap()).index(i)
     }
 }
 
-impl<V: VecType, T: Clone, S: SliceIndex<[T]>> IndexMut<S> for GenericVec<V, T> {
+impl<V: VecType, T: Clone, S: SliceIndex<[T]>, A: Allocator + Clone> IndexMut<S>
+    for GenericVec<V, T, A>
+{
     fn index_mut(&mut self, i: S) -> &mut Self::Output {
         VecMut::get_body_mut(self.base.try_make_vec_mut().unwrap()).index_mut(i)
     }
 }
 
-impl<V: VecType, T> Clone for GenericVec<V, T> {
+impl<V: VecType, T, A: Allocator + Clone> Clone for GenericVec<V, T, A> {
     fn clone(&self) -> Self {
         Self {
             base: self.base.clone(),
@@ -107,9 +520,11 @@ impl<V: VecType, T> Default for GenericVec<V, T> {
 }
 
 #[derive(Clone)]
-pub struct IntoIter<V: VecType, T>(header_slice::vec::IntoValuesIter<V::Counter, T>);
+pub struct IntoIter<V: VecType, T, A: Allocator + Clone = Global>(
+    pub(super) IntoValues<V::Counter, T, A>,
+);
 
-impl<V: VecType, T> Iterator for IntoIter<V, T> {
+impl<V: VecType, T, A: Allocator + Clone> Iterator for IntoIter<V, T, A> {
     type Item = T;
     fn next(&mut self) -> Option<T> {
         self.0.next()
@@ -120,11 +535,11 @@ impl<V: VecType, T> Iterator for IntoIter<V, T> {
     }
 }
 
-impl<V: VecType, T> ExactSizeIterator for IntoIter<V, T> {}
+impl<V: VecType, T, A: Allocator + Clone> ExactSizeIterator for IntoIter<V, T, A> {}
 
-impl<V: VecType, T: Clone> IntoIterator for GenericVec<V, T> {
+impl<V: VecType, T: Clone, A: Allocator + Clone> IntoIterator for GenericVec<V, T, A> {
     type Item = T;
-    type IntoIter = IntoIter<V, T>;
+    type IntoIter = IntoIter<V, T, A>;
     fn into_iter(self) -> Self::IntoIter {
         let vec = match self.base.try_into_vec() {
             Ok(v) => v,
@@ -134,38 +549,52 @@ impl<V: VecType, T: Clone> IntoIterator for GenericVec<V, T> {
     }
 }
 
-impl<'a, V: VecType, T> IntoIterator for &'a GenericVec<V, T> {
+impl<'a, V: VecType, T, A: Allocator + Clone> IntoIterator for &'a GenericVec<V, T, A> {
     type Item = &'a T;
     type IntoIter = core::slice::Iter<'a, T>;
     fn into_iter(self) -> Self::IntoIter {
-        (*self).deref().into_iter()
+        (*self).deref().iter()
     }
 }
 
-impl<'a, V: VecType, T: Clone> IntoIterator for &'a mut GenericVec<V, T> {
+impl<'a, V: VecType, T: Clone, A: Allocator + Clone> IntoIterator for &'a mut GenericVec<V, T, A> {
     type Item = &'a mut T;
     type IntoIter = core::slice::IterMut<'a, T>;
     fn into_iter(self) -> Self::IntoIter {
-        (*self).deref_mut().into_iter()
+        (*self).deref_mut().iter_mut()
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> Extend<T> for GenericVec<V, T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, it: I) {
+        let it = it.into_iter();
+        self.make_mut_with_room(it.size_hint().0).extend(it);
+    }
+}
+
+impl<'a, V: VecType, T: Copy + 'a, A: Allocator + Clone> Extend<&'a T> for GenericVec<V, T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, it: I) {
+        self.extend(it.into_iter().copied());
     }
 }
 
 impl<V: VecType, T> core::iter::FromIterator<T> for GenericVec<V, T> {
     fn from_iter<I: IntoIterator<Item = T>>(it: I) -> Self {
-        Self {
-            base: BaseRcVec::from_vec(it.into_iter().collect()),
-        }
+        Self::from_iter_in(it, Global)
     }
 }
 
-impl<V: VecType, T: fmt::Debug> fmt::Debug for GenericVec<V, T> {
+impl<V: VecType, T: fmt::Debug, A: Allocator + Clone> fmt::Debug for GenericVec<V, T, A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let slice: &[T] = self;
         fmt::Debug::fmt(slice, f)
     }
 }
 
-impl<V: VecType, T: PartialEq> PartialEq for GenericVec<V, T> {
+/// Compares the elements even if both handles point to the same vector, since `T` may not be
+/// reflexive: two clones of `[f64::NAN]` are not equal. Use `ptr_eq` to check for a shared vector
+/// in O(1).
+impl<V: VecType, T: PartialEq, A: Allocator + Clone> PartialEq for GenericVec<V, T, A> {
     fn eq(&self, rhs: &Self) -> bool {
         let s1: &[T] = self;
         let s2: &[T] = rhs;
@@ -173,9 +602,9 @@ impl<V: VecType, T: PartialEq> PartialEq for GenericVec<V, T> {
     }
 }
 
-impl<V: VecType, T: Eq> Eq for GenericVec<V, T> {}
+impl<V: VecType, T: Eq, A: Allocator + Clone> Eq for GenericVec<V, T, A> {}
 
-impl<V: VecType, T: PartialOrd> PartialOrd for GenericVec<V, T> {
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd for GenericVec<V, T, A> {
     fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
         let s1: &[T] = self;
         let s2: &[T] = rhs;
@@ -183,10 +612,207 @@ impl<V: VecType, T: PartialOrd> PartialOrd for GenericVec<V, T> {
     }
 }
 
-impl<V: VecType, T: Ord> Ord for GenericVec<V, T> {
+impl<V: VecType, T: Ord, A: Allocator + Clone> Ord for GenericVec<V, T, A> {
     fn cmp(&self, rhs: &Self) -> Ordering {
+        // unlike `PartialEq` and `PartialOrd`, `Ord` guarantees `T` is reflexive
+        if self.ptr_eq(rhs) {
+            return Ordering::Equal;
+        }
         let s1: &[T] = self;
         let s2: &[T] = rhs;
         s1.cmp(s2)
     }
 }
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone> PartialEq<[U]> for GenericVec<V, T, A> {
+    fn eq(&self, rhs: &[U]) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<'a, V: VecType, T: PartialEq<U>, U, A: Allocator + Clone> PartialEq<&'a [U]>
+    for GenericVec<V, T, A>
+{
+    fn eq(&self, rhs: &&'a [U]) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone> PartialEq<Vec<U>>
+    for GenericVec<V, T, A>
+{
+    fn eq(&self, rhs: &Vec<U>) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone, const N: usize> PartialEq<[U; N]>
+    for GenericVec<V, T, A>
+{
+    fn eq(&self, rhs: &[U; N]) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone> PartialEq<GenericVec<V, U, A>>
+    for [T]
+{
+    fn eq(&self, rhs: &GenericVec<V, U, A>) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone> PartialEq<GenericVec<V, U, A>>
+    for &[T]
+{
+    fn eq(&self, rhs: &GenericVec<V, U, A>) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone> PartialEq<GenericVec<V, U, A>>
+    for Vec<T>
+{
+    fn eq(&self, rhs: &GenericVec<V, U, A>) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialEq<U>, U, A: Allocator + Clone, const N: usize>
+    PartialEq<GenericVec<V, U, A>> for [T; N]
+{
+    fn eq(&self, rhs: &GenericVec<V, U, A>) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd<[T]> for GenericVec<V, T, A> {
+    fn partial_cmp(&self, rhs: &[T]) -> Option<Ordering> {
+        self[..].partial_cmp(rhs)
+    }
+}
+
+impl<'a, V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd<&'a [T]>
+    for GenericVec<V, T, A>
+{
+    fn partial_cmp(&self, rhs: &&'a [T]) -> Option<Ordering> {
+        self[..].partial_cmp(*rhs)
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd<Vec<T>> for GenericVec<V, T, A> {
+    fn partial_cmp(&self, rhs: &Vec<T>) -> Option<Ordering> {
+        self[..].partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone, const N: usize> PartialOrd<[T; N]>
+    for GenericVec<V, T, A>
+{
+    fn partial_cmp(&self, rhs: &[T; N]) -> Option<Ordering> {
+        self[..].partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd<GenericVec<V, T, A>> for [T] {
+    fn partial_cmp(&self, rhs: &GenericVec<V, T, A>) -> Option<Ordering> {
+        self.partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd<GenericVec<V, T, A>> for &[T] {
+    fn partial_cmp(&self, rhs: &GenericVec<V, T, A>) -> Option<Ordering> {
+        (**self).partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone> PartialOrd<GenericVec<V, T, A>> for Vec<T> {
+    fn partial_cmp(&self, rhs: &GenericVec<V, T, A>) -> Option<Ordering> {
+        self[..].partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: PartialOrd, A: Allocator + Clone, const N: usize>
+    PartialOrd<GenericVec<V, T, A>> for [T; N]
+{
+    fn partial_cmp(&self, rhs: &GenericVec<V, T, A>) -> Option<Ordering> {
+        self[..].partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: Hash, A: Allocator + Clone> Hash for GenericVec<V, T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        <[T]>::hash(self, state)
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> Borrow<[T]> for GenericVec<V, T, A> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> AsRef<[T]> for GenericVec<V, T, A> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<V: VecType, T> From<Vec<T>> for GenericVec<V, T> {
+    fn from(mut src: Vec<T>) -> Self {
+        let len = src.len();
+        // SAFETY: the elements are moved out and `src` forgets them, so it only frees its buffer
+        unsafe {
+            src.set_len(0);
+            Self::copy_from_ptr_unsafe(src.as_mut_ptr(), len)
+        }
+    }
+}
+
+impl<V: VecType, T> From<Box<[T]>> for GenericVec<V, T> {
+    fn from(src: Box<[T]>) -> Self {
+        src.into_vec().into()
+    }
+}
+
+impl<V: VecType, T: Clone> From<&[T]> for GenericVec<V, T> {
+    fn from(src: &[T]) -> Self {
+        src.iter().cloned().collect()
+    }
+}
+
+impl<V: VecType, T: Clone> From<Rc<[T]>> for GenericVec<V, T> {
+    fn from(src: Rc<[T]>) -> Self {
+        src.iter().cloned().collect()
+    }
+}
+
+impl<V: VecType, T: Clone> From<Arc<[T]>> for GenericVec<V, T> {
+    fn from(src: Arc<[T]>) -> Self {
+        src.iter().cloned().collect()
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> From<GenericVec<V, T, A>> for Vec<T> {
+    fn from(src: GenericVec<V, T, A>) -> Self {
+        src.unwrap_or_clone()
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> From<GenericVec<V, T, A>> for Box<[T]> {
+    fn from(src: GenericVec<V, T, A>) -> Self {
+        src.unwrap_or_clone().into_boxed_slice()
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> From<GenericVec<V, T, A>> for Rc<[T]> {
+    fn from(src: GenericVec<V, T, A>) -> Self {
+        src.unwrap_or_clone().into()
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> From<GenericVec<V, T, A>> for Arc<[T]> {
+    fn from(src: GenericVec<V, T, A>) -> Self {
+        src.unwrap_or_clone().into()
+    }
+}
@@ -0,0 +1,155 @@
+//! Vectors that carry user metadata in the shared header.
+//!
+//! `WithMeta<V, M>` counts handles exactly like `V`, but stores an `M` next to the counter, so
+//! data about the contents such as a content type or checksum needs no allocation of its own.
+//! Every handle to a buffer sees the same metadata. Changing it follows copy-on-write like the
+//! elements do, and a copy starts with a clone of the original's metadata.
+
+use super::counters::{Counter, VecType};
+use super::generic_vec::GenericVec;
+use crate::base::header_vec::HeaderVec;
+use crate::base::BaseRcVec;
+use crate::error::NotUnique;
+use allocator_api2::alloc::{Allocator, Global};
+use core::cell::UnsafeCell;
+use core::convert::Infallible;
+use core::marker::PhantomData;
+
+/// The counter used by vectors with metadata.
+pub struct MetaCounter<C, M> {
+    pub base: C,
+    /// Only written through a handle that `can_get_mut` allows to mutate the vector, so no other
+    /// handle can be reading it.
+    meta: UnsafeCell<M>,
+}
+
+// SAFETY: the metadata is only written while no other handle exists, so shared handles on other
+// threads only ever read it.
+unsafe impl<C: Sync, M: Send + Sync> Sync for MetaCounter<C, M> {}
+
+impl<C, M> MetaCounter<C, M> {
+    pub fn new(base: C, meta: M) -> Self {
+        Self {
+            base,
+            meta: UnsafeCell::new(meta),
+        }
+    }
+
+    pub fn meta(&self) -> &M {
+        // SAFETY: see the field
+        unsafe { &*self.meta.get() }
+    }
+}
+
+impl<C: Default, M: Default> Default for MetaCounter<C, M> {
+    fn default() -> Self {
+        Self::new(Default::default(), Default::default())
+    }
+}
+
+impl<C: Clone, M: Clone> Clone for MetaCounter<C, M> {
+    fn clone(&self) -> Self {
+        Self::new(self.base.clone(), self.meta().clone())
+    }
+}
+
+impl<C: Counter, M: Default + Clone> Counter for MetaCounter<C, M> {
+    fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+    fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+    fn for_copy(&self) -> Self {
+        Self::new(self.base.for_copy(), self.meta().clone())
+    }
+    fn reset(&mut self) {
+        self.base.reset();
+    }
+}
+
+/// The `VecType` of vectors that count handles like `V` and carry metadata `M`.
+pub struct WithMeta<V, M>(Infallible, PhantomData<(V, M)>);
+
+unsafe impl<V: VecType, M: Default + Clone> VecType for WithMeta<V, M> {
+    type Counter = MetaCounter<V::Counter, M>;
+
+    fn incr(counter: &Self::Counter) {
+        V::incr(&counter.base);
+    }
+    fn decr<F: FnOnce()>(counter: &Self::Counter, drop_contents: F) -> bool {
+        V::decr(&counter.base, drop_contents)
+    }
+    fn can_take(counter: &Self::Counter) -> bool {
+        V::can_take(&counter.base)
+    }
+    fn try_take<F: FnOnce()>(counter: &Self::Counter, take_contents: F) -> Option<bool> {
+        V::try_take(&counter.base, take_contents)
+    }
+    fn can_get_ref(counter: &Self::Counter) -> bool {
+        V::can_get_ref(&counter.base)
+    }
+    fn can_get_mut(counter: &Self::Counter) -> bool {
+        V::can_get_mut(&counter.base)
+    }
+    fn try_incr(counter: &Self::Counter) -> bool {
+        V::try_incr(&counter.base)
+    }
+    fn on_mut(counter: &Self::Counter) {
+        V::on_mut(&counter.base);
+    }
+}
+
+impl<V: VecType, M: Default + Clone, T> GenericVec<WithMeta<V, M>, T> {
+    pub fn with_meta(meta: M) -> Self {
+        Self::with_meta_in(meta, Global)
+    }
+}
+
+impl<V: VecType, M: Default + Clone, T, A: Allocator + Clone> GenericVec<WithMeta<V, M>, T, A> {
+    /// Unlike `new_in`, this always allocates a header to hold `meta`, but no room for elements.
+    pub fn with_meta_in(meta: M, alloc: A) -> Self {
+        let counter = MetaCounter::new(Default::default(), meta);
+        Self {
+            base: BaseRcVec::from_vec(HeaderVec::with_capacity_in(counter, 0, alloc)),
+        }
+    }
+
+    /// Returns the metadata shared by every handle to the vector.
+    /// An empty vector from `new` that hasn't allocated yet, or one borrowing static data, has no
+    /// header to store it in, so this returns `None` until it is first mutated; its metadata is
+    /// `M::default()`.
+    pub fn meta(&self) -> Option<&M> {
+        self.base.counter().map(MetaCounter::meta)
+    }
+
+    /// Returns the metadata for writing if this is the only handle to the vector.
+    pub fn get_meta_mut(&mut self) -> Result<&mut M, NotUnique> {
+        if !self.base.can_get_mut() {
+            return Err(NotUnique);
+        }
+        Ok(self.meta_mut_unchecked())
+    }
+
+    /// Allocates the header if this is the empty placeholder, then returns its metadata.
+    /// Must only be called when `can_get_mut` allows it.
+    fn meta_mut_unchecked(&mut self) -> &mut M {
+        // SAFETY: the caller checked that no other handle can access the vector
+        unsafe { self.base.unsafe_vec_mut() };
+        let counter = self.base.counter().unwrap();
+        // SAFETY: this handle is mutably borrowed and no other handle exists, so nothing else
+        // can read the metadata while the result exists
+        unsafe { &mut *counter.meta.get() }
+    }
+}
+
+impl<V: VecType, M: Default + Clone, T: Clone, A: Allocator + Clone>
+    GenericVec<WithMeta<V, M>, T, A>
+{
+    /// Returns the metadata for writing, copying the vector and its metadata first if it is
+    /// shared.
+    pub fn meta_mut(&mut self) -> &mut M {
+        assert!(self.base.try_make_unique());
+        self.meta_mut_unchecked()
+    }
+}
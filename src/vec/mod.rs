@@ -1,6 +1,40 @@
 pub mod strong_weak;
 pub mod counters;
+#[cfg(feature = "std")]
+pub mod cycle;
 pub mod generic_vec;
+pub mod meta;
+pub mod persistent;
+pub mod policy;
+pub mod segmented;
+pub mod slice;
+pub mod small;
+pub(crate) mod tree;
+pub mod unique;
 
-pub type AcycVec<T> = generic_vec::GenericVec<counters::AcycType, T>;
-pub type RcVec<T> = generic_vec::GenericVec<counters::StrongType, T>;
+use allocator_api2::alloc::Global;
+
+pub type AcycVec<T, A = Global> = generic_vec::GenericVec<counters::AcycType, T, A>;
+pub type RcVec<T, A = Global> = generic_vec::GenericVec<counters::StrongType, T, A>;
+pub type ArcAcycVec<T, A = Global> = generic_vec::GenericVec<counters::AtomicAcycType, T, A>;
+/// A vector that records a `counters::Version` whenever it is mutated.
+pub type VersionedRcVec<T, A = Global> = generic_vec::GenericVec<counters::VersionedType, T, A>;
+pub type ArcVec<T, A = Global> = generic_vec::GenericVec<counters::AtomicStrongType, T, A>;
+/// A vector that carries metadata `M` in its shared header.
+pub type MetaRcVec<T, M, A = Global> =
+    generic_vec::GenericVec<meta::WithMeta<counters::StrongType, M>, T, A>;
+pub type MetaArcVec<T, M, A = Global> =
+    generic_vec::GenericVec<meta::WithMeta<counters::AtomicStrongType, M>, T, A>;
+pub type UniqueAcycVec<T, A = Global> = unique::UniqueVec<counters::AcycType, T, A>;
+pub type UniqueRcVec<T, A = Global> = unique::UniqueVec<counters::StrongType, T, A>;
+pub type UniqueArcVec<T, A = Global> = unique::UniqueVec<counters::AtomicStrongType, T, A>;
+pub type RcSlice<T> = slice::GenericSlice<counters::StrongType, T>;
+pub type ArcSlice<T> = slice::GenericSlice<counters::AtomicStrongType, T>;
+pub type SmallRcVec<T, const N: usize = 2> = small::GenericSmallVec<counters::StrongType, T, N>;
+pub type SmallArcVec<T, const N: usize = 2> =
+    small::GenericSmallVec<counters::AtomicStrongType, T, N>;
+pub type SegRcVec<T, const N: usize = 1024> = segmented::GenericSegVec<counters::StrongType, T, N>;
+pub type SegArcVec<T, const N: usize = 1024> =
+    segmented::GenericSegVec<counters::AtomicStrongType, T, N>;
+pub type PVec<T> = persistent::GenericPVec<counters::StrongType, T>;
+pub type ArcPVec<T> = persistent::GenericPVec<counters::AtomicStrongType, T>;
@@ -0,0 +1,367 @@
+use super::generic_vec::GenericVec;
+use super::tree::{locate, Child, Leaf, Leaves, Node, SplitLeaf, Tree, BRANCH};
+use crate::base::VecType;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::Index;
+use core::slice;
+
+impl<V: VecType, T> Leaf for GenericVec<V, T> {
+    type Summary = usize;
+    fn summary(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<V: VecType, T: Clone> SplitLeaf for GenericVec<V, T> {
+    fn split_at(&self, at: usize) -> (Self, Self) {
+        (GenericVec::from(&self[..at]), GenericVec::from(&self[at..]))
+    }
+    fn merge(mut left: Self, right: Self) -> Result<Self, (Self, Self)> {
+        if left.len() + right.len() > BRANCH {
+            return Err((left, right));
+        }
+        left.extend(right.iter().cloned());
+        Ok(left)
+    }
+}
+
+/// A persistent vector: a tree of shared chunks of up to 32 elements, with every leaf at the same
+/// depth. Cloning is O(1), and modifying a clone only copies the O(log n) chunks on the path to
+/// the change, so older versions stay valid and cheap to keep.
+///
+/// Inner nodes record how many elements each child holds rather than requiring full leaves, so
+/// `split_at` and `concat` also only rebuild O(log n) chunks.
+pub struct GenericPVec<V: VecType, T> {
+    tree: Tree<V, GenericVec<V, T>>,
+}
+
+impl<V: VecType, T> GenericPVec<V, T> {
+    pub const fn new() -> Self {
+        Self {
+            tree: Tree {
+                root: Node::Leaf(GenericVec::new()),
+                height: 0,
+                sum: 0,
+            },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.sum
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.sum == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.tree.sum {
+            return None;
+        }
+        let (leaf, _, index) = self.tree.find_leaf(index, |len| len);
+        leaf.get(index)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.get(self.tree.sum.wrapping_sub(1))
+    }
+
+    pub fn iter(&self) -> Iter<'_, V, T> {
+        let mut leaves = self.tree.leaves();
+        Iter {
+            leaf: leaves.next().map_or([].iter(), |l| l.iter()),
+            leaves,
+            remaining: self.tree.sum,
+        }
+    }
+
+    /// Whether `get_mut(index)` would have to copy any chunks on the path to the element first.
+    /// Panics if `index` is out of bounds.
+    pub fn is_shared_at(&self, mut index: usize) -> bool {
+        assert!(
+            index < self.tree.sum,
+            "index {} is out of bounds for length {}",
+            index,
+            self.tree.sum
+        );
+        let mut node = &self.tree.root;
+        loop {
+            match node {
+                Node::Leaf(v) => return !v.is_unique(),
+                Node::Inner(children) => {
+                    if !children.is_unique() {
+                        return true;
+                    }
+                    let (i, before) = locate(children, index, |len| len);
+                    index -= before;
+                    node = &children[i].node;
+                }
+            }
+        }
+    }
+
+    /// Whether both vectors share the same root chunk, which means they have the same contents.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.tree.root, &other.tree.root) {
+            (Node::Leaf(a), Node::Leaf(b)) => a.ptr_eq(b),
+            (Node::Inner(a), Node::Inner(b)) => a.ptr_eq(b),
+            _ => false,
+        }
+    }
+}
+
+impl<V: VecType, T: Clone> GenericPVec<V, T> {
+    /// Returns the element at `index` for writing, copying the chunks on the path to it first
+    /// if they are shared.
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        if index >= self.tree.sum {
+            return None;
+        }
+        let mut node = &mut self.tree.root;
+        loop {
+            node = match node {
+                Node::Leaf(v) => return Some(&mut v.make_mut()[index]),
+                Node::Inner(children) => {
+                    let children = children.make_mut();
+                    let (i, before) = locate(children, index, |len| len);
+                    index -= before;
+                    &mut children[i].node
+                }
+            };
+        }
+    }
+
+    /// Replaces the element at `index`, returning the old one.
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, val: T) -> T {
+        match self.get_mut(index) {
+            Some(x) => mem::replace(x, val),
+            None => panic!(
+                "index {} is out of bounds for length {}",
+                index, self.tree.sum
+            ),
+        }
+    }
+
+    pub fn push_back(&mut self, val: T) {
+        if let Some(sibling) = Self::push_node(&mut self.tree.root, val) {
+            let old = mem::replace(&mut self.tree.root, Node::Leaf(GenericVec::new()));
+            let children = [
+                Child {
+                    sum: self.tree.sum,
+                    node: old,
+                },
+                sibling.into_child(),
+            ];
+            self.tree.root = Node::Inner(GenericVec::from_iter(children));
+            self.tree.height += 1;
+        }
+        self.tree.sum += 1;
+    }
+
+    /// Appends `val` to the last leaf under `node`, returning a new sibling for `node` if it is
+    /// already full.
+    fn push_node(
+        node: &mut Node<V, GenericVec<V, T>>,
+        val: T,
+    ) -> Option<Node<V, GenericVec<V, T>>> {
+        match node {
+            Node::Leaf(v) if v.len() < BRANCH => {
+                v.push(val);
+                None
+            }
+            Node::Leaf(_) => Some(Node::Leaf(GenericVec::from_iter([val]))),
+            Node::Inner(children) => {
+                let children = children.make_mut();
+                let last = children.last_mut().unwrap();
+                match Self::push_node(&mut last.node, val) {
+                    None => {
+                        last.sum += 1;
+                        None
+                    }
+                    Some(sibling) if children.len() < BRANCH => {
+                        children.push(sibling.into_child());
+                        None
+                    }
+                    Some(sibling) => {
+                        Some(Node::Inner(GenericVec::from_iter([sibling.into_child()])))
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.tree.sum == 0 {
+            return None;
+        }
+        let val = Self::pop_node(&mut self.tree.root);
+        if self.tree.sum == 1 {
+            *self = Self::new();
+        } else {
+            let root = mem::replace(&mut self.tree.root, Node::Leaf(GenericVec::new()));
+            self.tree = Tree::from_root(root, self.tree.height);
+        }
+        Some(val)
+    }
+
+    /// Removes the last element under `node`, which must not be empty, along with any chunks
+    /// that become empty.
+    fn pop_node(node: &mut Node<V, GenericVec<V, T>>) -> T {
+        match node {
+            Node::Leaf(v) => v.pop().unwrap(),
+            Node::Inner(children) => {
+                let children = children.make_mut();
+                let last = children.last_mut().unwrap();
+                let val = Self::pop_node(&mut last.node);
+                last.sum -= 1;
+                if last.sum == 0 {
+                    children.pop();
+                }
+                val
+            }
+        }
+    }
+
+    /// Returns the elements before `at` and the elements from `at` onwards.
+    /// Panics if `at > len`.
+    pub fn split_at(&self, at: usize) -> (Self, Self) {
+        assert!(
+            at <= self.tree.sum,
+            "split index {} is out of bounds for length {}",
+            at,
+            self.tree.sum
+        );
+        let (left, right) = self.tree.split_at(at);
+        (Self { tree: left }, Self { tree: right })
+    }
+
+    /// Returns a vector with the elements of `self` followed by those of `other`.
+    pub fn concat(&self, other: &Self) -> Self {
+        Self {
+            tree: self.tree.concat(&other.tree),
+        }
+    }
+}
+
+/// Iterates over the elements of a `GenericPVec` in order.
+pub struct Iter<'a, V: VecType, T> {
+    leaves: Leaves<'a, V, GenericVec<V, T>>,
+    leaf: slice::Iter<'a, T>,
+    remaining: usize,
+}
+
+impl<'a, V: VecType, T> Iterator for Iter<'a, V, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(x) = self.leaf.next() {
+                self.remaining -= 1;
+                return Some(x);
+            }
+            self.leaf = self.leaves.next()?.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, V: VecType, T> ExactSizeIterator for Iter<'a, V, T> {}
+
+impl<V: VecType, T> Clone for GenericPVec<V, T> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+impl<V: VecType, T> Default for GenericPVec<V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType, T> Index<usize> for GenericPVec<V, T> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(x) => x,
+            None => panic!(
+                "index {} is out of bounds for length {}",
+                index, self.tree.sum
+            ),
+        }
+    }
+}
+
+impl<V: VecType, T: Clone> Extend<T> for GenericPVec<V, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push_back(x);
+        }
+    }
+}
+
+impl<V: VecType, T: Clone> FromIterator<T> for GenericPVec<V, T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+impl<'a, V: VecType, T> IntoIterator for &'a GenericPVec<V, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, V, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V: VecType, T: fmt::Debug> fmt::Debug for GenericPVec<V, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<V: VecType, T: PartialEq> PartialEq for GenericPVec<V, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<V: VecType, T: Eq> Eq for GenericPVec<V, T> {}
+
+impl<V: VecType, T: PartialOrd> PartialOrd for GenericPVec<V, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<V: VecType, T: Ord> Ord for GenericPVec<V, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<V: VecType, T: Hash> Hash for GenericPVec<V, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // hash like a slice, so equal `Vec`s and `GenericPVec`s hash the same
+        self.len().hash(state);
+        for x in self {
+            x.hash(state);
+        }
+    }
+}
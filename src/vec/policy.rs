@@ -0,0 +1,108 @@
+//! Safe construction of custom reference-counting policies.
+//!
+//! A `Policy` layers extra state and restrictions on top of an existing `VecType`. Because it
+//! can only restrict what the base policy allows, it can't break the soundness guarantees the
+//! base provides, so implementing it requires no `unsafe`.
+
+use super::counters::{Counter, VecType};
+use super::generic_vec::GenericVec;
+use core::convert::Infallible;
+use core::marker::PhantomData;
+
+pub trait Policy {
+    /// The policy whose counting and permissions this one builds on.
+    type Base: VecType;
+    /// Extra state stored in the shared header next to the base counter.
+    /// A vector copied on write starts over with the default state.
+    type State: Default + Clone;
+
+    /// Whether a handle may mutate the vector in place.
+    /// Only consulted when the base policy already allows it; otherwise the vector is copied
+    /// before mutation.
+    fn allow_mut(_state: &Self::State, _counter: &<Self::Base as VecType>::Counter) -> bool {
+        true
+    }
+
+    /// Called after a new handle to the vector has been counted.
+    fn on_incr(_state: &Self::State, _counter: &<Self::Base as VecType>::Counter) {}
+
+    /// Called when a handle to the vector is released, before the contents are dropped or taken.
+    fn on_decr(_state: &Self::State, _counter: &<Self::Base as VecType>::Counter) {}
+}
+
+/// The counter used by vectors with a custom `Policy`.
+pub struct PolicyCounter<P: Policy> {
+    pub base: <P::Base as VecType>::Counter,
+    pub state: P::State,
+}
+
+impl<P: Policy> Default for PolicyCounter<P> {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            state: Default::default(),
+        }
+    }
+}
+
+impl<P: Policy> Clone for PolicyCounter<P> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<P: Policy> Counter for PolicyCounter<P> {
+    fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+    fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+}
+
+/// The `VecType` implemented by a `Policy`.
+pub struct WithPolicy<P: Policy>(Infallible, PhantomData<P>);
+
+unsafe impl<P: Policy> VecType for WithPolicy<P> {
+    type Counter = PolicyCounter<P>;
+
+    fn incr(counter: &PolicyCounter<P>) {
+        P::Base::incr(&counter.base);
+        P::on_incr(&counter.state, &counter.base);
+    }
+    fn decr<F: FnOnce()>(counter: &PolicyCounter<P>, drop_contents: F) -> bool {
+        P::on_decr(&counter.state, &counter.base);
+        P::Base::decr(&counter.base, drop_contents)
+    }
+    fn can_take(counter: &PolicyCounter<P>) -> bool {
+        P::Base::can_take(&counter.base) && P::allow_mut(&counter.state, &counter.base)
+    }
+    fn try_take<F: FnOnce()>(counter: &PolicyCounter<P>, take_contents: F) -> Option<bool> {
+        if !P::allow_mut(&counter.state, &counter.base) {
+            return None;
+        }
+        P::Base::try_take(&counter.base, || {
+            P::on_decr(&counter.state, &counter.base);
+            take_contents()
+        })
+    }
+    fn can_get_ref(counter: &PolicyCounter<P>) -> bool {
+        P::Base::can_get_ref(&counter.base)
+    }
+    fn can_get_mut(counter: &PolicyCounter<P>) -> bool {
+        P::Base::can_get_mut(&counter.base) && P::allow_mut(&counter.state, &counter.base)
+    }
+    fn try_incr(counter: &PolicyCounter<P>) -> bool {
+        if !P::Base::try_incr(&counter.base) {
+            return false;
+        }
+        P::on_incr(&counter.state, &counter.base);
+        true
+    }
+}
+
+/// A vector whose sharing behavior is controlled by the policy `P`.
+pub type PolicyVec<P, T> = GenericVec<WithPolicy<P>, T>;
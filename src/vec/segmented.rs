@@ -0,0 +1,321 @@
+use super::generic_vec::GenericVec;
+use super::persistent::GenericPVec;
+use crate::base::VecType;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{self, FromIterator};
+use core::mem;
+use core::ops::{Index, IndexMut};
+
+/// A vector stored as a list of shared chunks of `N` elements, so changing an element of a
+/// shared vector only copies the chunk holding it instead of the whole buffer.
+///
+/// Every chunk but the last is full, so finding an element only takes a division. The chunks
+/// are indexed by a `GenericPVec`, so a write to a shared vector also copies the O(log n) index
+/// nodes on the path to its chunk, each holding up to 32 chunk handles, but never the whole list.
+/// Mutating methods that can copy a chunk report whether they had to.
+///
+/// This is a separate type rather than a storage mode of `GenericVec` because a `GenericVec`
+/// derefs to one contiguous slice, which chunks in separate buffers can't provide. Reads go
+/// through `get` and the chunked iterators instead; `From<GenericVec>` and `to_flat` convert
+/// without copying while the contents fit in one chunk.
+pub struct GenericSegVec<V: VecType, T, const N: usize = 1024> {
+    chunks: GenericPVec<V, GenericVec<V, T>>,
+    len: usize,
+}
+
+impl<V: VecType, T, const N: usize> GenericSegVec<V, T, N> {
+    const CHUNK_NONZERO: () = assert!(N > 0, "chunks must hold at least one element");
+
+    pub fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CHUNK_NONZERO;
+        Self {
+            chunks: GenericPVec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.chunks.get(index / N)?.get(index % N)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.chunks.last()?.last()
+    }
+
+    pub fn iter(&self) -> Iter<'_, V, T> {
+        self.chunks().flatten()
+    }
+
+    /// Iterates over the chunks in order, each as a slice.
+    pub fn chunks(&self) -> Chunks<'_, V, T> {
+        Chunks {
+            chunks: &self.chunks,
+            front: 0,
+            back: self.chunks.len(),
+        }
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Whether changing an element of chunk `chunk` would copy it first. That is the case if
+    /// the chunk is shared, or if an index node on the path to it is, since copying that node
+    /// shares every chunk under it.
+    /// Panics if `chunk` is out of bounds.
+    pub fn is_chunk_shared(&self, chunk: usize) -> bool {
+        self.chunks.is_shared_at(chunk) || !self.chunks[chunk].is_unique()
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> GenericSegVec<V, T, N> {
+    /// Returns chunk `chunk` for writing, copying it first if it is shared, along with whether
+    /// it was copied. Panics if `chunk` is out of bounds.
+    pub fn chunk_mut(&mut self, chunk: usize) -> (&mut [T], bool) {
+        let copied = self.is_chunk_shared(chunk);
+        (&mut self.chunk_handle_mut(chunk)[..], copied)
+    }
+
+    /// Returns the handle to chunk `chunk`, which must be in bounds, copying the index nodes on
+    /// the path to it if they are shared.
+    fn chunk_handle_mut(&mut self, chunk: usize) -> &mut GenericVec<V, T> {
+        self.chunks.get_mut(chunk).unwrap()
+    }
+
+    /// Returns the element at `index` for writing, copying the chunk holding it first if it is
+    /// shared.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&mut self.chunk_mut(index / N).0[index % N])
+    }
+
+    /// Replaces the element at `index`, returning the old one and whether the chunk holding it
+    /// had to be copied. Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, val: T) -> (T, bool) {
+        self.check_index(index);
+        let (chunk, copied) = self.chunk_mut(index / N);
+        (mem::replace(&mut chunk[index % N], val), copied)
+    }
+
+    fn check_index(&self, index: usize) {
+        assert!(
+            index < self.len,
+            "index {} is out of bounds for length {}",
+            index,
+            self.len
+        );
+    }
+
+    pub fn push(&mut self, val: T) {
+        if self.len % N == 0 {
+            let mut chunk = GenericVec::with_capacity(N);
+            chunk.push(val);
+            self.chunks.push_back(chunk);
+        } else {
+            let last = self.chunks.len() - 1;
+            self.chunk_handle_mut(last).push(val);
+        }
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let last = self.chunks.len() - 1;
+        let val = self.chunk_handle_mut(last).pop();
+        if self.chunks[last].is_empty() {
+            self.chunks.pop_back();
+        }
+        self.len -= 1;
+        val
+    }
+
+    /// Shortens the vector to `len` elements. Does nothing if it is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.chunks = self.chunks.split_at(len.div_ceil(N)).0;
+        if len % N != 0 {
+            let last = self.chunks.len() - 1;
+            self.chunk_handle_mut(last).truncate(len % N);
+        }
+        self.len = len;
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Copies the elements into a single contiguous vector. A vector with only one chunk returns
+    /// it without copying.
+    pub fn to_flat(&self) -> GenericVec<V, T> {
+        match self.chunks.len() {
+            0 => GenericVec::new(),
+            1 => self.chunks[0].clone(),
+            _ => self.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Iterates over the chunks of a `GenericSegVec`.
+pub struct Chunks<'a, V: VecType, T> {
+    chunks: &'a GenericPVec<V, GenericVec<V, T>>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, V: VecType, T> Iterator for Chunks<'a, V, T> {
+    type Item = &'a [T];
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(&self.chunks[self.front - 1])
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, V: VecType, T> DoubleEndedIterator for Chunks<'a, V, T> {
+    fn next_back(&mut self) -> Option<&'a [T]> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(&self.chunks[self.back])
+    }
+}
+
+impl<'a, V: VecType, T> ExactSizeIterator for Chunks<'a, V, T> {}
+
+pub type Iter<'a, V, T> = iter::Flatten<Chunks<'a, V, T>>;
+
+impl<V: VecType, T, const N: usize> Clone for GenericSegVec<V, T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<V: VecType, T, const N: usize> Default for GenericSegVec<V, T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType, T, const N: usize> Index<usize> for GenericSegVec<V, T, N> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(x) => x,
+            None => panic!("index {} is out of bounds for length {}", index, self.len),
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> IndexMut<usize> for GenericSegVec<V, T, N> {
+    /// Copies only the chunk holding `index` if it is shared.
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.check_index(index);
+        &mut self.chunk_mut(index / N).0[index % N]
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> From<GenericVec<V, T>> for GenericSegVec<V, T, N> {
+    /// Reuses the buffer if it fits in one chunk; otherwise copies the elements into chunks.
+    fn from(src: GenericVec<V, T>) -> Self {
+        if src.len() > N {
+            return src.iter().cloned().collect();
+        }
+        let mut this = Self::new();
+        this.len = src.len();
+        if !src.is_empty() {
+            this.chunks.push_back(src);
+        }
+        this
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> From<&[T]> for GenericSegVec<V, T, N> {
+    fn from(src: &[T]) -> Self {
+        src.iter().cloned().collect()
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> From<Vec<T>> for GenericSegVec<V, T, N> {
+    fn from(src: Vec<T>) -> Self {
+        src.into_iter().collect()
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> Extend<T> for GenericSegVec<V, T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, it: I) {
+        for x in it {
+            self.push(x);
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> FromIterator<T> for GenericSegVec<V, T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(it: I) -> Self {
+        let mut this = Self::new();
+        this.extend(it);
+        this
+    }
+}
+
+impl<'a, V: VecType, T, const N: usize> IntoIterator for &'a GenericSegVec<V, T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, V, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V: VecType, T: fmt::Debug, const N: usize> fmt::Debug for GenericSegVec<V, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<V: VecType, T: PartialEq, const N: usize> PartialEq for GenericSegVec<V, T, N> {
+    fn eq(&self, rhs: &Self) -> bool {
+        // both are split at the same indices, so the chunks line up
+        self.len == rhs.len && self.chunks().eq(rhs.chunks())
+    }
+}
+
+impl<V: VecType, T: Eq, const N: usize> Eq for GenericSegVec<V, T, N> {}
+
+impl<V: VecType, T: Hash, const N: usize> Hash for GenericSegVec<V, T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for x in self {
+            x.hash(state);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use super::generic_vec::GenericVec;
+use crate::base::header_vec::HeaderVec;
+use crate::base::vec_ref::{VecMut, VecRef};
+use crate::base::{BaseRcVec, VecType};
+use allocator_api2::alloc::Global;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::ops::{Bound, Deref, DerefMut, RangeBounds};
+
+/// Resolves `range` against a sequence of length `len`, panicking like slice indexing if it is
+/// out of bounds.
+pub(crate) fn resolve_range<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i.checked_add(1).expect("range start overflowed"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&i) => i.checked_add(1).expect("range end overflowed"),
+        Bound::Excluded(&i) => i,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range starts at {} but ends at {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "range end {} is out of bounds for length {}",
+        end,
+        len
+    );
+    (start, end)
+}
+
+/// A window into a shared vector that keeps the whole vector alive without copying it.
+/// Mutating a slice copies just the window unless this is the only handle to the vector.
+pub struct GenericSlice<V: VecType, T> {
+    base: BaseRcVec<V, T>,
+    start: usize,
+    len: usize,
+}
+
+impl<V: VecType, T> GenericVec<V, T> {
+    /// Returns a shared handle to the elements in `range`.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> GenericSlice<V, T> {
+        GenericSlice::from(self.clone()).slice(range)
+    }
+
+    /// Splits the vector into two shared handles at `mid`.
+    /// Unlike `[T]::split_at`, the halves don't borrow from `self`.
+    pub fn split_at_shared(&self, mid: usize) -> (GenericSlice<V, T>, GenericSlice<V, T>) {
+        GenericSlice::from(self.clone()).split_at_shared(mid)
+    }
+
+    /// Returns an iterator of shared handles to consecutive chunks of `size` elements.
+    /// The last chunk may be shorter.
+    pub fn chunks_shared(&self, size: usize) -> ChunksShared<V, T> {
+        GenericSlice::from(self.clone()).chunks_shared(size)
+    }
+}
+
+impl<V: VecType, T> GenericSlice<V, T> {
+    fn full(&self) -> &[T] {
+        VecRef::get_body(self.base.try_vec_ref().unwrap())
+    }
+
+    /// Returns a shared handle to the elements in `range`, relative to this slice.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let (start, end) = resolve_range(range, self.len);
+        Self {
+            base: self.base.clone(),
+            start: self.start + start,
+            len: end - start,
+        }
+    }
+
+    /// Splits the slice into two shared handles at `mid`.
+    pub fn split_at_shared(&self, mid: usize) -> (Self, Self) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Returns an iterator of shared handles to consecutive chunks of `size` elements.
+    /// The last chunk may be shorter.
+    pub fn chunks_shared(self, size: usize) -> ChunksShared<V, T> {
+        assert!(size != 0, "chunk size must be non-zero");
+        ChunksShared { rest: self, size }
+    }
+
+    /// Whether both slices cover the same elements of the same vector.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base) && self.start == other.start && self.len == other.len
+    }
+}
+
+impl<V: VecType, T> From<GenericVec<V, T>> for GenericSlice<V, T> {
+    fn from(src: GenericVec<V, T>) -> Self {
+        let len = src.len();
+        Self {
+            base: src.base,
+            start: 0,
+            len,
+        }
+    }
+}
+
+impl<V: VecType, T: Clone> From<GenericSlice<V, T>> for GenericVec<V, T> {
+    /// Reuses the vector if the slice covers all of it; otherwise copies the window.
+    fn from(src: GenericSlice<V, T>) -> Self {
+        if src.start == 0 && src.len == src.full().len() {
+            GenericVec { base: src.base }
+        } else {
+            src.iter().cloned().collect()
+        }
+    }
+}
+
+impl<V: VecType, T> Deref for GenericSlice<V, T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.full()[self.start..self.start + self.len]
+    }
+}
+
+impl<V: VecType, T: Clone> DerefMut for GenericSlice<V, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        if !self.base.can_get_mut() {
+            // copy only the window rather than the whole vector
+            let copy =
+                HeaderVec::from_iter_in(self.base.counter_for_copy(), self.iter().cloned(), Global);
+            self.base = BaseRcVec::from_vec(copy);
+            self.start = 0;
+        }
+        let body = VecMut::get_body_mut(self.base.try_make_vec_mut().unwrap());
+        &mut body[self.start..self.start + self.len]
+    }
+}
+
+impl<V: VecType, T> Clone for GenericSlice<V, T> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            start: self.start,
+            len: self.len,
+        }
+    }
+}
+
+impl<V: VecType, T> Default for GenericSlice<V, T> {
+    fn default() -> Self {
+        GenericVec::new().into()
+    }
+}
+
+impl<'a, V: VecType, T> IntoIterator for &'a GenericSlice<V, T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V: VecType, T: fmt::Debug> fmt::Debug for GenericSlice<V, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let slice: &[T] = self;
+        fmt::Debug::fmt(slice, f)
+    }
+}
+
+impl<V: VecType, T: PartialEq> PartialEq for GenericSlice<V, T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        let s1: &[T] = self;
+        let s2: &[T] = rhs;
+        s1 == s2
+    }
+}
+
+impl<V: VecType, T: Eq> Eq for GenericSlice<V, T> {}
+
+impl<V: VecType, T: PartialOrd> PartialOrd for GenericSlice<V, T> {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        let s1: &[T] = self;
+        let s2: &[T] = rhs;
+        s1.partial_cmp(s2)
+    }
+}
+
+impl<V: VecType, T: Ord> Ord for GenericSlice<V, T> {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        if self.ptr_eq(rhs) {
+            return Ordering::Equal;
+        }
+        let s1: &[T] = self;
+        let s2: &[T] = rhs;
+        s1.cmp(s2)
+    }
+}
+
+impl<V: VecType, T: Hash> Hash for GenericSlice<V, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        <[T]>::hash(self, state)
+    }
+}
+
+impl<V: VecType, T> Borrow<[T]> for GenericSlice<V, T> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<V: VecType, T> AsRef<[T]> for GenericSlice<V, T> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+/// Iterator returned by `chunks_shared`.
+pub struct ChunksShared<V: VecType, T> {
+    rest: GenericSlice<V, T>,
+    size: usize,
+}
+
+impl<V: VecType, T> Iterator for ChunksShared<V, T> {
+    type Item = GenericSlice<V, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.len == 0 {
+            return None;
+        }
+        let (chunk, rest) = self.rest.split_at_shared(self.size.min(self.rest.len));
+        self.rest = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.rest.len.div_ceil(self.size);
+        (n, Some(n))
+    }
+}
+
+impl<V: VecType, T> ExactSizeIterator for ChunksShared<V, T> {}
+
+impl<V: VecType, T> FusedIterator for ChunksShared<V, T> {}
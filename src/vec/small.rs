@@ -0,0 +1,281 @@
+use super::generic_vec::GenericVec;
+use crate::base::VecType;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+use core::{ptr, slice};
+
+/// Up to `N` elements stored directly in the handle.
+struct InlineVec<T, const N: usize> {
+    len: u8,
+    buf: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> InlineVec<T, N> {
+    const CAP_FITS: () = assert!(N <= u8::MAX as usize, "inline capacity must fit in a u8");
+
+    fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_FITS;
+        Self {
+            len: 0,
+            // SAFETY: an array of `MaybeUninit` doesn't need to be initialized
+            buf: unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() },
+        }
+    }
+
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { slice::from_raw_parts(self.buf.as_ptr() as *const T, self.len as usize) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut T, self.len as usize) }
+    }
+
+    /// Pushes `val`, or gives it back if there is no room.
+    fn push(&mut self, val: T) -> Result<(), T> {
+        match self.buf.get_mut(self.len as usize) {
+            Some(slot) => {
+                *slot = MaybeUninit::new(val);
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(val),
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        // SAFETY: the element was initialized, and is no longer counted by `len`
+        Some(unsafe { self.buf[self.len as usize].as_ptr().read() })
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.len as usize {
+            let old_len = self.len as usize;
+            self.len = len as u8;
+            // SAFETY: these elements were initialized, and are no longer counted by `len`
+            unsafe {
+                let tail = &mut self.buf[len..old_len] as *mut [MaybeUninit<T>] as *mut [T];
+                ptr::drop_in_place(tail);
+            }
+        }
+    }
+
+    /// Moves every element into `dest`, leaving this empty.
+    fn move_into<E: Extend<T>>(&mut self, dest: &mut E) {
+        let len = self.len as usize;
+        self.len = 0;
+        // SAFETY: the elements were initialized, and are no longer counted by `len`
+        dest.extend((0..len).map(|i| unsafe { self.buf[i].as_ptr().read() }));
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for InlineVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut this = Self::new();
+        for x in self.as_slice() {
+            let _ = this.push(x.clone());
+        }
+        this
+    }
+}
+
+impl<T, const N: usize> Drop for InlineVec<T, N> {
+    fn drop(&mut self) {
+        self.truncate(0);
+    }
+}
+
+enum Repr<V: VecType, T, const N: usize> {
+    Inline(InlineVec<T, N>),
+    Heap(GenericVec<V, T>),
+}
+
+/// A vector that stores up to `N` elements in the handle itself, and moves them into a shared
+/// `GenericVec` once it grows past that. Inline vectors are copied when cloned, so call
+/// `make_shared` first if a small vector will be cloned many times.
+pub struct GenericSmallVec<V: VecType, T, const N: usize> {
+    repr: Repr<V, T, N>,
+}
+
+impl<V: VecType, T, const N: usize> GenericSmallVec<V, T, N> {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline(InlineVec::new()),
+        }
+    }
+
+    /// Whether the elements are stored in the handle rather than a shared buffer.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline(_))
+    }
+
+    /// Moves the elements into a shared buffer, if they aren't in one already, so clones of
+    /// this handle share them.
+    pub fn make_shared(&mut self) {
+        if let Repr::Inline(inline) = &mut self.repr {
+            let mut heap = GenericVec::with_capacity(inline.len as usize);
+            inline.move_into(heap.unique_mut().unwrap());
+            self.repr = Repr::Heap(heap);
+        }
+    }
+
+    /// Converts this into a shared vector, moving the elements into one if they are inline.
+    pub fn into_shared(mut self) -> GenericVec<V, T> {
+        self.make_shared();
+        match self.repr {
+            Repr::Heap(heap) => heap,
+            Repr::Inline(_) => unreachable!("vector was just made shared"),
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> GenericSmallVec<V, T, N> {
+    pub fn push(&mut self, val: T) {
+        let val = match &mut self.repr {
+            Repr::Inline(inline) => match inline.push(val) {
+                Ok(()) => return,
+                Err(val) => val,
+            },
+            Repr::Heap(heap) => return heap.push(val),
+        };
+        // out of room, so move to the heap with room to grow
+        if let Repr::Inline(inline) = &mut self.repr {
+            let mut heap = GenericVec::with_capacity(N * 2 + 1);
+            inline.move_into(heap.unique_mut().unwrap());
+            heap.push(val);
+            self.repr = Repr::Heap(heap);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.pop(),
+            Repr::Heap(heap) => heap.pop(),
+        }
+    }
+
+    /// Shortens the vector to `len` elements. Does nothing if it is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.truncate(len),
+            Repr::Heap(heap) => heap.truncate(len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<V: VecType, T, const N: usize> Deref for GenericSmallVec<V, T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        match &self.repr {
+            Repr::Inline(inline) => inline.as_slice(),
+            Repr::Heap(heap) => heap,
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> DerefMut for GenericSmallVec<V, T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match &mut self.repr {
+            Repr::Inline(inline) => inline.as_mut_slice(),
+            Repr::Heap(heap) => heap,
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> Clone for GenericSmallVec<V, T, N> {
+    fn clone(&self) -> Self {
+        let repr = match &self.repr {
+            Repr::Inline(inline) => Repr::Inline(inline.clone()),
+            Repr::Heap(heap) => Repr::Heap(heap.clone()),
+        };
+        Self { repr }
+    }
+}
+
+impl<V: VecType, T, const N: usize> Default for GenericSmallVec<V, T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType, T, const N: usize> From<GenericVec<V, T>> for GenericSmallVec<V, T, N> {
+    fn from(src: GenericVec<V, T>) -> Self {
+        Self {
+            repr: Repr::Heap(src),
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> From<&[T]> for GenericSmallVec<V, T, N> {
+    fn from(src: &[T]) -> Self {
+        src.iter().cloned().collect()
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> Extend<T> for GenericSmallVec<V, T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, it: I) {
+        for x in it {
+            self.push(x);
+        }
+    }
+}
+
+impl<V: VecType, T: Clone, const N: usize> FromIterator<T> for GenericSmallVec<V, T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(it: I) -> Self {
+        let mut this = Self::new();
+        this.extend(it);
+        this
+    }
+}
+
+impl<'a, V: VecType, T, const N: usize> IntoIterator for &'a GenericSmallVec<V, T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V: VecType, T: fmt::Debug, const N: usize> fmt::Debug for GenericSmallVec<V, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let slice: &[T] = self;
+        fmt::Debug::fmt(slice, f)
+    }
+}
+
+impl<V: VecType, T: PartialEq, const N: usize> PartialEq for GenericSmallVec<V, T, N> {
+    fn eq(&self, rhs: &Self) -> bool {
+        self[..] == rhs[..]
+    }
+}
+
+impl<V: VecType, T: Eq, const N: usize> Eq for GenericSmallVec<V, T, N> {}
+
+impl<V: VecType, T: PartialOrd, const N: usize> PartialOrd for GenericSmallVec<V, T, N> {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        self[..].partial_cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: Ord, const N: usize> Ord for GenericSmallVec<V, T, N> {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        self[..].cmp(&rhs[..])
+    }
+}
+
+impl<V: VecType, T: Hash, const N: usize> Hash for GenericSmallVec<V, T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        <[T]>::hash(self, state)
+    }
+}
@@ -1,6 +1,6 @@
-use super::counters::WeakType;
+use super::counters::{AtomicWeakType, WeakType};
 use super::generic_vec::GenericVec;
-use super::RcVec;
+use super::{ArcVec, RcVec};
 use crate::base::BaseRcVec;
 use core::fmt;
 
@@ -22,6 +22,23 @@ impl<T> WeakVec<T> {
             base: self.base.try_convert()?,
         })
     }
+
+    pub fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+
+    pub fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+
+    /// Returns a pointer to the first element. Only dereference it while the vector is alive.
+    pub fn as_ptr(&self) -> *const T {
+        self.base.as_ptr()
+    }
 }
 
 impl<T> Clone for WeakVec<T> {
@@ -45,3 +62,62 @@ impl<T: fmt::Debug> fmt::Debug for WeakVec<T> {
         Ok(())
     }
 }
+
+impl<T> ArcVec<T> {
+    pub fn downgrade(&self) -> ArcWeakVec<T> {
+        ArcWeakVec {
+            base: self.base.try_convert().unwrap(),
+        }
+    }
+}
+
+pub struct ArcWeakVec<T> {
+    base: BaseRcVec<AtomicWeakType, T>,
+}
+
+impl<T> ArcWeakVec<T> {
+    pub fn upgrade(&self) -> Option<ArcVec<T>> {
+        Some(GenericVec {
+            base: self.base.try_convert()?,
+        })
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.base.strong_count()
+    }
+
+    pub fn weak_count(&self) -> usize {
+        self.base.weak_count()
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.base.ptr_eq(&other.base)
+    }
+
+    /// Returns a pointer to the first element. Only dereference it while the vector is alive.
+    pub fn as_ptr(&self) -> *const T {
+        self.base.as_ptr()
+    }
+}
+
+impl<T> Clone for ArcWeakVec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcWeakVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ArcWeakVec(")?;
+
+        match self.upgrade() {
+            Some(v) => fmt::Debug::fmt(&v, f)?,
+            None => f.write_str("<dropped>")?,
+        }
+
+        f.write_str(")")?;
+        Ok(())
+    }
+}
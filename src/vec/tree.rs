@@ -0,0 +1,373 @@
+//! The balanced tree of shared chunks behind `GenericPVec` and `GenericRope`.
+//!
+//! Every leaf is at the same depth, and inner nodes hold up to `BRANCH` children along with a
+//! summary of what is under each, so lookups can skip whole subtrees. Splitting and joining
+//! trees only rebuild the O(log n) nodes along the edge where they are cut or meet; everything
+//! else stays shared with the originals.
+
+use super::generic_vec::GenericVec;
+use crate::base::VecType;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::FromIterator;
+use core::ops::{Add, AddAssign};
+use core::slice;
+
+/// The most children of an inner node.
+pub(crate) const BRANCH: usize = 32;
+
+/// What a tree records about each subtree.
+pub(crate) trait Summary: Copy + Default + Add<Output = Self> + AddAssign {
+    /// The length that offsets passed to `Tree::split_at` count.
+    fn len(self) -> usize;
+}
+
+impl Summary for usize {
+    fn len(self) -> usize {
+        self
+    }
+}
+
+/// A chunk stored in the leaves of a tree.
+pub(crate) trait Leaf: Clone + Default {
+    type Summary: Summary;
+    fn summary(&self) -> Self::Summary;
+}
+
+/// A leaf that can be cut and merged, which splitting and joining trees needs.
+pub(crate) trait SplitLeaf: Leaf {
+    /// Splits the leaf at `at`, which is counted by `Summary::len` and strictly inside it.
+    fn split_at(&self, at: usize) -> (Self, Self);
+    /// Merges two adjacent leaves into one if the result is small enough for a leaf.
+    fn merge(left: Self, right: Self) -> Result<Self, (Self, Self)>;
+}
+
+pub(crate) enum Node<V: VecType, L: Leaf> {
+    Leaf(L),
+    Inner(GenericVec<V, Child<V, L>>),
+}
+
+pub(crate) struct Child<V: VecType, L: Leaf> {
+    /// The summary of everything under `node`.
+    pub(crate) sum: L::Summary,
+    pub(crate) node: Node<V, L>,
+}
+
+impl<V: VecType, L: Leaf> Clone for Node<V, L> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Leaf(l) => Node::Leaf(l.clone()),
+            Node::Inner(v) => Node::Inner(v.clone()),
+        }
+    }
+}
+
+impl<V: VecType, L: Leaf> Clone for Child<V, L> {
+    fn clone(&self) -> Self {
+        Self {
+            sum: self.sum,
+            node: self.node.clone(),
+        }
+    }
+}
+
+impl<V: VecType, L: Leaf> Node<V, L> {
+    pub(crate) fn summary(&self) -> L::Summary {
+        match self {
+            Node::Leaf(l) => l.summary(),
+            Node::Inner(v) => v.iter().fold(L::Summary::default(), |acc, c| acc + c.sum),
+        }
+    }
+
+    pub(crate) fn into_child(self) -> Child<V, L> {
+        Child {
+            sum: self.summary(),
+            node: self,
+        }
+    }
+}
+
+/// Returns the child containing the `index`th unit counted by `metric`, and the summary of the
+/// children before it.
+pub(crate) fn locate<V: VecType, L: Leaf>(
+    children: &[Child<V, L>],
+    mut index: usize,
+    metric: fn(L::Summary) -> usize,
+) -> (usize, L::Summary) {
+    let mut before = L::Summary::default();
+    for (i, c) in children.iter().enumerate() {
+        if index < metric(c.sum) {
+            return (i, before);
+        }
+        index -= metric(c.sum);
+        before += c.sum;
+    }
+    unreachable!("index is past the end of the node")
+}
+
+/// Splits `children` into two nodes if there are too many of them for one.
+fn split_overflow<V: VecType, L: Leaf>(
+    mut children: GenericVec<V, Child<V, L>>,
+) -> (Node<V, L>, Option<Node<V, L>>) {
+    if children.len() <= BRANCH {
+        return (Node::Inner(children), None);
+    }
+    let right = children.split_off(children.len() / 2);
+    (Node::Inner(children), Some(Node::Inner(right)))
+}
+
+pub(crate) struct Tree<V: VecType, L: Leaf> {
+    pub(crate) root: Node<V, L>,
+    /// The number of inner levels above the leaves.
+    pub(crate) height: usize,
+    pub(crate) sum: L::Summary,
+}
+
+impl<V: VecType, L: Leaf> Clone for Tree<V, L> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            height: self.height,
+            sum: self.sum,
+        }
+    }
+}
+
+impl<V: VecType, L: Leaf> Tree<V, L> {
+    pub(crate) fn new() -> Self {
+        Self {
+            root: Node::Leaf(L::default()),
+            height: 0,
+            sum: L::Summary::default(),
+        }
+    }
+
+    /// Wraps a subtree, dropping inner levels that only have one child.
+    pub(crate) fn from_root(mut root: Node<V, L>, mut height: usize) -> Self {
+        while let Node::Inner(children) = &root {
+            if children.len() != 1 {
+                break;
+            }
+            root = children[0].node.clone();
+            height -= 1;
+        }
+        let sum = root.summary();
+        Self { root, height, sum }
+    }
+
+    /// Builds a tree from its leaves in order, sharing them rather than copying.
+    pub(crate) fn from_leaves<I: IntoIterator<Item = L>>(leaves: I) -> Self {
+        let mut level: Vec<_> = leaves
+            .into_iter()
+            .map(|l| Node::Leaf(l).into_child())
+            .collect();
+        let mut height = 0;
+        while level.len() > 1 {
+            level = level
+                .chunks(BRANCH)
+                .map(|c| Node::Inner(GenericVec::from(c)).into_child())
+                .collect();
+            height += 1;
+        }
+        match level.pop() {
+            Some(c) => Self::from_root(c.node, height),
+            None => Self::new(),
+        }
+    }
+
+    /// Descends to the leaf holding the `index`th unit counted by `metric`, which must be in
+    /// bounds. Returns the leaf, the summary of everything before it, and the index within it.
+    pub(crate) fn find_leaf(
+        &self,
+        mut index: usize,
+        metric: fn(L::Summary) -> usize,
+    ) -> (&L, L::Summary, usize) {
+        let mut before = L::Summary::default();
+        let mut node = &self.root;
+        loop {
+            match node {
+                Node::Leaf(l) => return (l, before, index),
+                Node::Inner(children) => {
+                    let (i, b) = locate(children, index, metric);
+                    index -= metric(b);
+                    before += b;
+                    node = &children[i].node;
+                }
+            }
+        }
+    }
+
+    /// Iterates over the leaves in order.
+    pub(crate) fn leaves(&self) -> Leaves<'_, V, L> {
+        let mut iter = Leaves {
+            stack: Vec::new(),
+            leaf: None,
+        };
+        iter.descend(&self.root);
+        iter
+    }
+}
+
+impl<V: VecType, L: SplitLeaf> Tree<V, L> {
+    /// Returns the tree before `at` and the tree from `at` onwards, with offsets counted by
+    /// `Summary::len`. `at` must be in bounds.
+    pub(crate) fn split_at(&self, at: usize) -> (Self, Self) {
+        if at == 0 {
+            return (Self::new(), self.clone());
+        }
+        if at == self.sum.len() {
+            return (self.clone(), Self::new());
+        }
+        let (left, right) = Self::split_node(&self.root, at);
+        (
+            Self::from_root(left, self.height),
+            Self::from_root(right, self.height),
+        )
+    }
+
+    /// Splits a node at `at`, which must be strictly inside it, into two nodes of the same height.
+    fn split_node(node: &Node<V, L>, at: usize) -> (Node<V, L>, Node<V, L>) {
+        match node {
+            Node::Leaf(l) => {
+                let (a, b) = l.split_at(at);
+                (Node::Leaf(a), Node::Leaf(b))
+            }
+            Node::Inner(children) => {
+                let (i, before) = locate(children, at, Summary::len);
+                let at = at - before.len();
+                let mut left: GenericVec<V, Child<V, L>> = GenericVec::from(&children[..i]);
+                let mut right = GenericVec::new();
+                if at == 0 {
+                    right.extend(children[i..].iter().cloned());
+                } else {
+                    let (l, r) = Self::split_node(&children[i].node, at);
+                    left.push(l.into_child());
+                    right.push(r.into_child());
+                    right.extend(children[i + 1..].iter().cloned());
+                }
+                (Node::Inner(left), Node::Inner(right))
+            }
+        }
+    }
+
+    /// Returns a tree with the contents of `self` followed by those of `other`.
+    pub(crate) fn concat(&self, other: &Self) -> Self {
+        if self.sum.len() == 0 {
+            return other.clone();
+        }
+        if other.sum.len() == 0 {
+            return self.clone();
+        }
+        let height = self.height.max(other.height);
+        let joined = Self::join(
+            self.root.clone(),
+            self.height,
+            other.root.clone(),
+            other.height,
+        );
+        match joined {
+            (root, None) => Self::from_root(root, height),
+            (left, Some(right)) => {
+                let children = [left.into_child(), right.into_child()];
+                Self::from_root(Node::Inner(GenericVec::from_iter(children)), height + 1)
+            }
+        }
+    }
+
+    /// Joins two subtrees into one or two nodes as tall as the taller of them, copying only the
+    /// chunks along the edge where they meet.
+    fn join(
+        left: Node<V, L>,
+        left_height: usize,
+        right: Node<V, L>,
+        right_height: usize,
+    ) -> (Node<V, L>, Option<Node<V, L>>) {
+        match Ord::cmp(&left_height, &right_height) {
+            Ordering::Equal => match (left, right) {
+                (Node::Leaf(a), Node::Leaf(b)) => match L::merge(a, b) {
+                    Ok(l) => (Node::Leaf(l), None),
+                    Err((a, b)) => (Node::Leaf(a), Some(Node::Leaf(b))),
+                },
+                (Node::Inner(mut a), Node::Inner(b)) if a.len() + b.len() <= BRANCH => {
+                    a.extend(b.iter().cloned());
+                    (Node::Inner(a), None)
+                }
+                (a, b) => (a, Some(b)),
+            },
+            Ordering::Greater => {
+                let mut children = match left {
+                    Node::Inner(children) => children,
+                    Node::Leaf(_) => unreachable!("leaves have height 0"),
+                };
+                let last = children.pop().unwrap();
+                let (a, b) = Self::join(last.node, left_height - 1, right, right_height);
+                children.push(a.into_child());
+                if let Some(b) = b {
+                    children.push(b.into_child());
+                }
+                split_overflow(children)
+            }
+            Ordering::Less => {
+                let mut children = match right {
+                    Node::Inner(children) => children,
+                    Node::Leaf(_) => unreachable!("leaves have height 0"),
+                };
+                let first = children.remove(0).unwrap();
+                let (a, b) = Self::join(left, left_height, first.node, right_height - 1);
+                if let Some(b) = b {
+                    children.insert(0, b.into_child());
+                }
+                children.insert(0, a.into_child());
+                split_overflow(children)
+            }
+        }
+    }
+}
+
+/// Iterates over the leaves of a `Tree` in order.
+pub(crate) struct Leaves<'a, V: VecType, L: Leaf> {
+    /// The remaining children of each inner node above the current leaf.
+    stack: Vec<slice::Iter<'a, Child<V, L>>>,
+    leaf: Option<&'a L>,
+}
+
+impl<'a, V: VecType, L: Leaf> Leaves<'a, V, L> {
+    /// Moves to the first leaf under `node`.
+    fn descend(&mut self, mut node: &'a Node<V, L>) {
+        loop {
+            match node {
+                Node::Leaf(l) => {
+                    self.leaf = Some(l);
+                    return;
+                }
+                Node::Inner(children) => {
+                    let mut iter = children.iter();
+                    node = match iter.next() {
+                        Some(c) => &c.node,
+                        None => return,
+                    };
+                    self.stack.push(iter);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, V: VecType, L: Leaf> Iterator for Leaves<'a, V, L> {
+    type Item = &'a L;
+
+    fn next(&mut self) -> Option<&'a L> {
+        loop {
+            if let Some(l) = self.leaf.take() {
+                return Some(l);
+            }
+            let next = self.stack.last_mut()?.next();
+            match next {
+                Some(c) => self.descend(&c.node),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use super::generic_vec::{GenericVec, IntoIter};
+use super::slice::resolve_range;
+use crate::base::header_vec::HeaderVec;
+use crate::base::vec_ref::{VecMut, VecRef};
+use crate::base::{self, BaseRcVec, VecType};
+use alloc::vec;
+use allocator_api2::alloc::{Allocator, Global};
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{Deref, DerefMut, RangeBounds};
+
+/// A vector that is known to have no other handles, so it can be mutated in place without
+/// requiring `T: Clone`. Freeze it into a shareable `GenericVec` with `freeze`, which doesn't
+/// copy anything.
+#[repr(transparent)]
+pub struct UniqueVec<V: VecType, T, A: Allocator + Clone = Global> {
+    pub(super) base: BaseRcVec<V, T, A>,
+}
+
+impl<V: VecType, T> UniqueVec<V, T> {
+    /// Doesn't allocate until the vector is first mutated.
+    pub const fn new() -> Self {
+        Self {
+            base: BaseRcVec::new(),
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> UniqueVec<V, T, A> {
+    /// Unlike `new`, this allocates a header to hold `alloc`, but no room for elements.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_capacity_in(0, alloc)
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Self {
+            base: BaseRcVec::from_vec(HeaderVec::with_capacity_in(Default::default(), cap, alloc)),
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.base.allocator()
+    }
+
+    fn vec_mut(&mut self) -> VecMut<'_, V::Counter, T, A> {
+        // SAFETY: no other handle to the vector exists while this one does
+        unsafe { self.base.unsafe_vec_mut() }
+    }
+
+    /// Converts this into a shareable vector without copying.
+    pub fn freeze(self) -> GenericVec<V, T, A> {
+        GenericVec { base: self.base }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.base.try_vec_ref().unwrap().capacity()
+    }
+
+    pub fn push(&mut self, val: T) {
+        self.vec_mut().push(val);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec_mut().pop()
+    }
+
+    pub fn insert(&mut self, index: usize, val: T) {
+        self.vec_mut().insert(index, val);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.vec_mut().remove(index)
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        self.vec_mut().swap_remove(index)
+    }
+
+    /// Shortens the vector to `len` elements. Does nothing if it is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.vec_mut().truncate(len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vec_mut().clear();
+    }
+
+    pub fn resize_with(&mut self, new_len: usize, f: impl FnMut() -> T) {
+        self.vec_mut().resize_with(new_len, f);
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec_mut().reserve(additional);
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.vec_mut().reserve_exact(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.vec_mut().shrink_to_fit();
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.retain_mut(|x| f(x));
+    }
+
+    /// Like `retain`, but `f` may mutate the elements it visits.
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len() {
+            if f(&mut self[i]) {
+                self.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns `true`, keeping the first of
+    /// each run. `same_bucket` is passed the element being checked, then the last kept element.
+    pub fn dedup_by(&mut self, mut same_bucket: impl FnMut(&mut T, &mut T) -> bool) {
+        if self.len() <= 1 {
+            return;
+        }
+        let mut kept = 1;
+        for i in 1..self.len() {
+            let (prev, rest) = self.split_at_mut(i);
+            if !same_bucket(&mut rest[0], &mut prev[kept - 1]) {
+                self.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    /// Removes consecutive elements that map to the same key, keeping the first of each run.
+    pub fn dedup_by_key<K: PartialEq>(&mut self, mut key: impl FnMut(&mut T) -> K) {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes the elements in `range` and returns them in order.
+    /// Unlike `Vec::drain`, the elements are removed immediately rather than as the iterator is
+    /// consumed.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> vec::IntoIter<T> {
+        let (start, end) = resolve_range(range, self.len());
+        // move the drained elements to the end so they can be taken in one go
+        self[start..].rotate_left(end - start);
+        let at = self.len() - (end - start);
+        base::move_tail_into_vec(&mut self.vec_mut(), at).into_iter()
+    }
+
+    /// Replaces the elements in `range` with `replace_with`, returning the removed elements.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> vec::IntoIter<T>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let (start, end) = resolve_range(range, self.len());
+        let removed = self.drain(start..end);
+        let old_len = self.len();
+        self.extend(replace_with);
+        let inserted = self.len() - old_len;
+        self[start..].rotate_right(inserted);
+        removed
+    }
+
+    /// Moves the elements from `at` onwards into a new vector.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let tail = base::move_tail_into_vec(&mut self.vec_mut(), at);
+        let alloc = self.allocator().clone();
+        let counter = self.base.counter_for_copy();
+        Self {
+            base: BaseRcVec::from_vec(HeaderVec::from_iter_in(counter, tail, alloc)),
+        }
+    }
+
+    /// Moves every element of `other` onto the end of this vector, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        let moved = base::move_tail_into_vec(&mut other.vec_mut(), 0);
+        self.reserve(moved.len());
+        self.extend(moved);
+    }
+}
+
+impl<V: VecType, T: Clone, A: Allocator + Clone> UniqueVec<V, T, A> {
+    pub fn resize(&mut self, new_len: usize, val: T) {
+        self.vec_mut().resize(new_len, val);
+    }
+
+    /// Clones the elements in `range` onto the end of the vector.
+    pub fn extend_from_within<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = resolve_range(range, self.len());
+        self.reserve(end - start);
+        for i in start..end {
+            let val = self[i].clone();
+            self.push(val);
+        }
+    }
+}
+
+impl<V: VecType, T: Copy, A: Allocator + Clone> UniqueVec<V, T, A> {
+    pub fn extend_from_slice(&mut self, src: &[T]) {
+        self.vec_mut().extend_from_slice(src);
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> From<UniqueVec<V, T, A>> for GenericVec<V, T, A> {
+    fn from(src: UniqueVec<V, T, A>) -> Self {
+        src.freeze()
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> Deref for UniqueVec<V, T, A> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        VecRef::get_body(self.base.try_vec_ref().unwrap())
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> DerefMut for UniqueVec<V, T, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        VecMut::get_body_mut(self.vec_mut())
+    }
+}
+
+impl<V: VecType, T> Default for UniqueVec<V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> Extend<T> for UniqueVec<V, T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, it: I) {
+        self.vec_mut().extend(it);
+    }
+}
+
+impl<V: VecType, T> FromIterator<T> for UniqueVec<V, T> {
+    fn from_iter<I: IntoIterator<Item = T>>(it: I) -> Self {
+        Self {
+            base: BaseRcVec::from_vec(HeaderVec::from_iter_in(Default::default(), it, Global)),
+        }
+    }
+}
+
+impl<V: VecType, T, A: Allocator + Clone> IntoIterator for UniqueVec<V, T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<V, T, A>;
+    fn into_iter(self) -> Self::IntoIter {
+        let vec = match self.base.try_take_vec() {
+            Ok(v) => v,
+            Err(_) => unreachable!("UniqueVec was somehow shared"),
+        };
+        IntoIter(vec.into_values())
+    }
+}
+
+impl<'a, V: VecType, T, A: Allocator + Clone> IntoIterator for &'a UniqueVec<V, T, A> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V: VecType, T, A: Allocator + Clone> IntoIterator for &'a mut UniqueVec<V, T, A> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<V: VecType, T: fmt::Debug, A: Allocator + Clone> fmt::Debug for UniqueVec<V, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let slice: &[T] = self;
+        fmt::Debug::fmt(slice, f)
+    }
+}
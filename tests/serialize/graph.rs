@@ -0,0 +1,97 @@
+use rc_vec::rc_vec;
+use rc_vec::serialize::SharedGraph;
+use rc_vec::string::{RcString, StrongRcString};
+use rc_vec::vec::strong_weak::WeakVec;
+use rc_vec::vec::{AcycVec, RcVec};
+use serde::{Serialize, Serializer};
+
+#[test]
+fn clones_are_shared() {
+    let a = rc_vec![1, 2];
+    let b = rc_vec![3];
+    let vs = [a.clone(), b.clone(), a, b];
+    let json = serde_json::to_string(&SharedGraph(&vs)).unwrap();
+    assert_eq!(json, r#"[{"Def":[1,2]},{"Def":[3]},{"Ref":0},{"Ref":1}]"#);
+
+    let SharedGraph(vs): SharedGraph<Vec<RcVec<i32>>> = serde_json::from_str(&json).unwrap();
+    assert!(vs[0].ptr_eq(&vs[2]));
+    assert!(vs[1].ptr_eq(&vs[3]));
+    assert!(!vs[0].ptr_eq(&vs[1]));
+    assert_eq!(vs[0].strong_count(), 2);
+}
+
+#[test]
+fn nested() {
+    let inner = rc_vec![1];
+    let outer = rc_vec![inner.clone(), inner];
+    let vs = [outer.clone(), outer];
+    let json = serde_json::to_string(&SharedGraph(&vs)).unwrap();
+    assert_eq!(json, r#"[{"Def":[{"Def":[1]},{"Ref":1}]},{"Ref":0}]"#);
+
+    let SharedGraph(vs): SharedGraph<Vec<RcVec<RcVec<i32>>>> = serde_json::from_str(&json).unwrap();
+    assert!(vs[0].ptr_eq(&vs[1]));
+    assert!(vs[0][0].ptr_eq(&vs[0][1]));
+}
+
+#[test]
+fn strings() {
+    let s = RcString::from("foo");
+    let ss = [s.clone(), s];
+    let json = serde_json::to_string(&SharedGraph(&ss)).unwrap();
+    assert_eq!(json, r#"[{"Def":"foo"},{"Ref":0}]"#);
+    let SharedGraph(ss): SharedGraph<Vec<RcString>> = serde_json::from_str(&json).unwrap();
+    assert!(ss[0].ptr_eq(&ss[1]));
+}
+
+#[test]
+fn weak_stays_alive() {
+    let v = rc_vec![1, 2];
+    let data = (v.downgrade(), v);
+    let json = serde_json::to_string(&SharedGraph(&data)).unwrap();
+    assert_eq!(json, r#"[{"Def":[1,2]},{"Ref":0}]"#);
+
+    let SharedGraph((w, v)): SharedGraph<(WeakVec<i32>, RcVec<i32>)> =
+        serde_json::from_str(&json).unwrap();
+    assert!(w.upgrade().unwrap().ptr_eq(&v));
+    assert_eq!((v.strong_count(), v.weak_count()), (1, 1));
+
+    let s = StrongRcString::from("foo");
+    let json = serde_json::to_string(&SharedGraph((s.downgrade(), &s))).unwrap();
+    assert_eq!(json, r#"[{"Def":"foo"},{"Ref":0}]"#);
+}
+
+#[test]
+fn type_mismatch() {
+    let json = r#"[{"Def":[1,2]},{"Ref":0}]"#;
+    let res: Result<SharedGraph<(RcVec<i32>, AcycVec<i32>)>, _> = serde_json::from_str(json);
+    assert!(res.is_err());
+    let res: Result<SharedGraph<Vec<RcVec<i32>>>, _> = serde_json::from_str(r#"[{"Ref":0}]"#);
+    assert!(res.is_err());
+}
+
+#[test]
+fn placeholders_not_shared() {
+    let a = rc_vec![1];
+    let vs = [RcVec::new(), RcVec::new(), a.clone(), a];
+    let json = serde_json::to_string(&SharedGraph(&vs)).unwrap();
+    assert_eq!(json, r#"[{"Def":[]},{"Def":[]},{"Def":[1]},{"Ref":2}]"#);
+
+    let SharedGraph(vs): SharedGraph<Vec<RcVec<i32>>> = serde_json::from_str(&json).unwrap();
+    assert!(vs[2].ptr_eq(&vs[3]));
+}
+
+/// Serializes as a vector that only exists while it is being written.
+struct Temporary(i32);
+
+impl Serialize for Temporary {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        rc_vec![self.0].serialize(s)
+    }
+}
+
+#[test]
+fn freed_addresses_not_reused() {
+    let ts: Vec<_> = (0..4).map(Temporary).collect();
+    let json = serde_json::to_string(&SharedGraph(&ts)).unwrap();
+    assert_eq!(json, r#"[{"Def":[0]},{"Def":[1]},{"Def":[2]},{"Def":[3]}]"#);
+}
@@ -0,0 +1,7 @@
+//! The `serde` tests live outside the library so that `serde_json`'s comparison impls don't
+//! affect type inference in the library's own tests.
+#![cfg(feature = "serde")]
+
+#[cfg(feature = "std")]
+mod graph;
+mod plain;
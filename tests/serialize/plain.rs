@@ -0,0 +1,56 @@
+use rc_vec::rc_str;
+use rc_vec::rc_vec;
+use rc_vec::string::{RcString, StrongRcString};
+use rc_vec::vec::strong_weak::WeakVec;
+use rc_vec::vec::{AcycVec, RcVec};
+
+#[test]
+fn round_trip_vec() {
+    let v = rc_vec![1, 2, 3];
+    let json = serde_json::to_string(&v).unwrap();
+    assert_eq!(json, "[1,2,3]");
+    let v2: RcVec<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(v, v2);
+    let v3: AcycVec<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(*v3, [1, 2, 3]);
+}
+
+#[test]
+fn round_trip_string() {
+    let s = rc_str!("héllo");
+    let json = serde_json::to_string(&s).unwrap();
+    assert_eq!(json, "\"héllo\"");
+    let s2: RcString = serde_json::from_str(&json).unwrap();
+    assert_eq!(s, s2);
+}
+
+#[test]
+fn clones_are_copied() {
+    let v = rc_vec![1, 2];
+    let vs = [v.clone(), v];
+    let json = serde_json::to_string(&vs).unwrap();
+    assert_eq!(json, "[[1,2],[1,2]]");
+    let vs: Vec<RcVec<i32>> = serde_json::from_str(&json).unwrap();
+    assert!(!vs[0].ptr_eq(&vs[1]));
+}
+
+#[test]
+fn weak() {
+    let v = rc_vec![1, 2];
+    let w = v.downgrade();
+    assert_eq!(serde_json::to_string(&w).unwrap(), "[1,2]");
+
+    // nothing keeps the deserialized value alive
+    let w: WeakVec<i32> = serde_json::from_str("[1,2]").unwrap();
+    assert!(w.upgrade().is_none());
+    let w2: WeakVec<i32> = serde_json::from_str("null").unwrap();
+    assert!(w2.upgrade().is_none());
+
+    drop(v);
+    assert_eq!(serde_json::to_string(&w).unwrap(), "null");
+    let w = RcVec::<i32>::new().downgrade();
+    assert_eq!(serde_json::to_string(&w).unwrap(), "null");
+
+    let s = StrongRcString::from("foo");
+    assert_eq!(serde_json::to_string(&s.downgrade()).unwrap(), "\"foo\"");
+}